@@ -5,8 +5,17 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::handle::{
-    cargo::CargoPackageManager, docker::DockerPackageManager, gradle::GradlePackageManager,
-    maven::MavenPackageManager, npm::NpmPackageManager, pip::PipPackageManager, MirrorConfigurate,
+    apk::ApkPackageManager, apt::AptPackageManager, bun::BunPackageManager,
+    cargo::CargoPackageManager, composer::ComposerPackageManager, conda::CondaPackageManager,
+    containerd::ContainerdPackageManager, cpan::CpanPackageManager, cran::CranPackageManager,
+    dnf::DnfPackageManager, docker::DockerPackageManager, flutter::FlutterPackageManager,
+    gem::GemPackageManager, go::GoPackageManager, gradle::GradlePackageManager,
+    haskell::HaskellPackageManager, helm::HelmPackageManager, hex::HexPackageManager,
+    homebrew::HomebrewPackageManager, julia::JuliaPackageManager, k3s::K3sPackageManager,
+    maven::MavenPackageManager, nix::NixPackageManager, npm::NpmPackageManager,
+    nuget::NugetPackageManager, opam::OpamPackageManager, pacman::PacmanPackageManager,
+    pip::PipPackageManager, podman::PodmanPackageManager, rustup::RustupPackageManager,
+    yarn::YarnPackageManager, MirrorConfigurate,
 };
 
 /// 选择内置镜像源
@@ -143,8 +152,37 @@ pub(crate) fn process() {
     let npm = NpmPackageManager {};
     let pip = PipPackageManager {};
     let docker = DockerPackageManager {};
+    let apt = AptPackageManager {};
+    let pacman = PacmanPackageManager {};
+    let dnf = DnfPackageManager {};
+    let apk = ApkPackageManager {};
+    let go = GoPackageManager {};
+    let conda = CondaPackageManager {};
+    let yarn = YarnPackageManager {};
+    let bun = BunPackageManager {};
+    let rustup = RustupPackageManager {};
+    let homebrew = HomebrewPackageManager {};
+    let gem = GemPackageManager {};
+    let composer = ComposerPackageManager {};
+    let nuget = NugetPackageManager {};
+    let flutter = FlutterPackageManager {};
+    let hex = HexPackageManager {};
+    let haskell = HaskellPackageManager {};
+    let cran = CranPackageManager {};
+    let julia = JuliaPackageManager {};
+    let cpan = CpanPackageManager {};
+    let opam = OpamPackageManager {};
+    let nix = NixPackageManager {};
+    let podman = PodmanPackageManager {};
+    let containerd = ContainerdPackageManager {};
+    let k3s = K3sPackageManager {};
+    let helm = HelmPackageManager {};
 
-    parse_command!(cargo, mvn, gradle, npm, pip, docker);
+    parse_command!(
+        cargo, mvn, gradle, npm, pip, docker, apt, pacman, dnf, apk, go, conda, yarn, bun, rustup,
+        homebrew, gem, composer, nuget, flutter, hex, haskell, cran, julia, cpan, opam, nix,
+        podman, containerd, k3s, helm
+    );
 }
 
 pub(crate) fn read_mix_config() -> MixConfig {
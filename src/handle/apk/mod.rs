@@ -0,0 +1,280 @@
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use crate::utils::net_utils::test_connection;
+use anyhow::{bail, Result};
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::file_utils::read_config;
+
+use super::{write_config, MirrorConfigurate, Reader};
+
+pub(crate) use os_specific::*;
+
+/// 官方 CDN 地址
+const DEFAULT_APK_MIRROR: &str = "https://dl-cdn.alpinelinux.org";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct ApkMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl ApkMirror {
+    pub(crate) fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for ApkMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for ApkMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 解析 repositories 中的 main/community 仓库行，返回镜像站地址、仓库地址及其在行中的位置
+///
+/// 形如 `@edge https://dl-cdn.alpinelinux.org/alpine/edge/community`
+fn parse_repository_line(line: &str) -> Option<(&str, usize, &str)> {
+    let mut tokens = line.split_whitespace();
+    let mut url = tokens.next()?;
+    if url.starts_with('@') {
+        url = tokens.next()?;
+    }
+    let repo = url.trim_end_matches('/').rsplit('/').next()?;
+    if repo != "main" && repo != "community" {
+        return None;
+    }
+    let (base, _) = url.split_once("/alpine/")?;
+    Some((base, line.find(url)?, url))
+}
+
+///
+/// 替换 main/community 仓库的镜像站地址，保留版本分支与 @tag 前缀
+///
+fn replace_repositories(content: &str, base: &str) -> String {
+    let base = base.trim_end_matches('/');
+    let mut new_content = String::new();
+    for line in content.lines() {
+        match parse_repository_line(line) {
+            Some((old_base, start, url)) if !line.trim_start().starts_with('#') => {
+                new_content.push_str(&line[..start]);
+                new_content.push_str(base);
+                new_content.push_str(&url[old_base.len()..]);
+                new_content.push_str(&line[start + url.len()..]);
+            }
+            _ => new_content.push_str(line),
+        }
+        new_content.push('\n');
+    }
+    new_content
+}
+
+#[cfg(target_os = "linux")]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    static DEFAULT_APK_PROFILE: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+        let path = PathBuf::from("/etc/apk/repositories");
+        vec![path]
+    });
+
+    impl Reader for ApkMirror {
+        fn new_config(&self) -> Result<String> {
+            if let Ok((_, repositories)) = read_config(DEFAULT_APK_PROFILE.to_vec()) {
+                return Ok(replace_repositories(&repositories, &self.url));
+            }
+            // 根据 /etc/alpine-release 中的版本号生成配置，如 3.20.3 对应 v3.20
+            match fs::read_to_string("/etc/alpine-release") {
+                Ok(release) => {
+                    let branch = match release.trim().split('.').collect::<Vec<_>>()[..] {
+                        [major, minor, ..] if !minor.contains('_') => {
+                            format!("v{}.{}", major, minor)
+                        }
+                        _ => "edge".to_string(),
+                    };
+                    Ok(format!(
+                        include_str!("../../../templates/repositories"),
+                        self.url.trim_end_matches('/'),
+                        branch
+                    ))
+                }
+                Err(_) => bail!("Not an Alpine Linux distribution"),
+            }
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct ApkPackageManager {}
+
+    impl MirrorConfigurate for ApkPackageManager {
+        type R = ApkMirror;
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![arg!(-u --url <URL>).help("mirror url").required(true)]
+        }
+
+        fn name(&self) -> &'static str {
+            "apk"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            if let Ok((_, repositories)) = read_config(self.get_default_profile_vec()) {
+                return repositories
+                    .lines()
+                    .filter(|line| !line.trim_start().starts_with('#'))
+                    .find_map(parse_repository_line)
+                    .map(|(base, _, _)| ApkMirror::new(base.to_string()));
+            }
+            None
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            let mirrors = include_str!("../../../mirrors/apk.json");
+            let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+            mirrors
+                .into_iter()
+                .map(|x| {
+                    let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                        delay as i128
+                    } else {
+                        -1
+                    };
+                    Self::R { url_delay, ..x }
+                })
+                .collect()
+        }
+
+        fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+            let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+            let mirror = ApkMirror::new(url);
+            self.set_mirror(mirror);
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            DEFAULT_APK_PROFILE.to_vec()
+        }
+
+        fn remove_mirror(&self, mirror: Self::R) {
+            if self
+                .current_mirror()
+                .is_some_and(|current| current.url == mirror.url)
+            {
+                self.reset_mirrors();
+            }
+        }
+
+        fn reset_mirrors(&self) {
+            if let Ok((_, repositories)) = read_config(self.get_default_profile_vec()) {
+                let new_config = replace_repositories(&repositories, DEFAULT_APK_MIRROR);
+                let _ = write_config(self.get_default_profile_vec(), &new_config);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    impl Reader for ApkMirror {
+        fn new_config(&self) -> Result<String> {
+            unimplemented!("not support new_config for this platform")
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct ApkPackageManager {}
+
+    impl MirrorConfigurate for ApkPackageManager {
+        type R = ApkMirror;
+
+        fn support(&self) -> bool {
+            false
+        }
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![arg!(-u --url <URL>).help("mirror url").required(true)]
+        }
+
+        fn name(&self) -> &'static str {
+            "apk"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            unimplemented!("not support current_mirror for this platform")
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            unimplemented!("not support get_mirrors for this platform")
+        }
+
+        fn set_mirror_by_args(&self, _args: &clap::ArgMatches) {
+            unimplemented!("not support set_mirror_by_args for this platform")
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            unimplemented!("not support get_default_profile_vec for this platform")
+        }
+
+        fn remove_mirror(&self, _mirror: Self::R) {
+            unimplemented!("not support remove_mirror for this platform")
+        }
+
+        fn reset_mirrors(&self) {
+            unimplemented!("not support reset_mirrors for this platform")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_host() {
+        let repositories = r#"https://dl-cdn.alpinelinux.org/alpine/v3.20/main
+https://dl-cdn.alpinelinux.org/alpine/v3.20/community
+#https://dl-cdn.alpinelinux.org/alpine/edge/main
+@edge https://dl-cdn.alpinelinux.org/alpine/edge/community
+@testing https://dl-cdn.alpinelinux.org/alpine/edge/testing
+"#;
+        let new_repositories =
+            replace_repositories(repositories, "https://mirrors.tuna.tsinghua.edu.cn/");
+        assert_eq!(
+            new_repositories,
+            r#"https://mirrors.tuna.tsinghua.edu.cn/alpine/v3.20/main
+https://mirrors.tuna.tsinghua.edu.cn/alpine/v3.20/community
+#https://dl-cdn.alpinelinux.org/alpine/edge/main
+@edge https://mirrors.tuna.tsinghua.edu.cn/alpine/edge/community
+@testing https://dl-cdn.alpinelinux.org/alpine/edge/testing
+"#
+        );
+        assert_eq!(
+            replace_repositories(&new_repositories, DEFAULT_APK_MIRROR),
+            repositories
+        );
+    }
+}
@@ -1,2 +1,562 @@
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use crate::utils::net_utils::test_connection;
+use anyhow::{bail, Result};
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::utils::file_utils::read_config;
+
+use super::{write_config, MirrorConfigurate, Reader};
+
+pub(crate) use os_specific::*;
+
+/// 镜像站上各发行版仓库对应的目录名称
+const DISTRO_DIRS: [&str; 3] = ["ubuntu", "ubuntu-ports", "debian"];
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct AptMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl AptMirror {
+    pub(crate) fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for AptMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for AptMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// /etc/os-release 中的发行版信息
+///
+#[derive(Debug, PartialEq, Eq)]
+struct OsRelease {
+    /// ubuntu 或 debian（衍生发行版取 ID_LIKE）
+    id: String,
+    codename: String,
+}
+
+impl OsRelease {
+    fn parse(content: &str) -> Option<Self> {
+        let mut id = String::new();
+        let mut id_like = String::new();
+        let mut version_codename = String::new();
+        let mut ubuntu_codename = String::new();
+        for line in content.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim().trim_matches('"').to_string();
+                match key.trim() {
+                    "ID" => id = value,
+                    "ID_LIKE" => id_like = value,
+                    "VERSION_CODENAME" => version_codename = value,
+                    "UBUNTU_CODENAME" => ubuntu_codename = value,
+                    _ => {}
+                }
+            }
+        }
+        let id = if id == "ubuntu" || id == "debian" {
+            id
+        } else if id_like.split_whitespace().any(|x| x == "ubuntu") {
+            "ubuntu".to_string()
+        } else if id_like.split_whitespace().any(|x| x == "debian") {
+            "debian".to_string()
+        } else {
+            return None;
+        };
+        // 衍生发行版（如 Linux Mint）的 VERSION_CODENAME 是自身代号，需要使用 UBUNTU_CODENAME
+        let codename = if ubuntu_codename.is_empty() {
+            version_codename
+        } else {
+            ubuntu_codename
+        };
+        if codename.is_empty() {
+            None
+        } else {
+            Some(Self { id, codename })
+        }
+    }
+}
+
+///
+/// 官方仓库地址
+///
+fn official_base(dir: &str) -> &'static str {
+    match dir {
+        "ubuntu-ports" => "http://ports.ubuntu.com",
+        "debian" => "http://deb.debian.org",
+        _ => "http://archive.ubuntu.com",
+    }
+}
+
+///
+/// 安全更新仓库保持使用官方地址
+///
+fn is_security(suite: &str) -> bool {
+    suite.ends_with("-security") || suite.ends_with("/updates")
+}
+
+///
+/// 获取发行版仓库地址对应的镜像站地址及目录名称
+///
+/// 只处理形如 `scheme://host/ubuntu` 的地址，避免误改 docker、ppa 等第三方源
+fn split_uri(uri: &str) -> Option<(String, String)> {
+    let parsed = Url::parse(uri).ok()?;
+    let segments: Vec<&str> = parsed.path_segments()?.filter(|s| !s.is_empty()).collect();
+    let [dir] = segments[..] else {
+        return None;
+    };
+    if !DISTRO_DIRS.contains(&dir) {
+        return None;
+    }
+    let base = uri.trim_end_matches('/').strip_suffix(dir)?;
+    Some((base.trim_end_matches('/').to_string(), dir.to_string()))
+}
+
+///
+/// 替换仓库地址的主机部分，base 为 None 时恢复为官方地址
+///
+fn replace_uri(uri: &str, base: Option<&str>) -> Option<String> {
+    let (_, dir) = split_uri(uri)?;
+    let base = base.unwrap_or(official_base(&dir)).trim_end_matches('/');
+    let slash = if uri.ends_with('/') { "/" } else { "" };
+    Some(format!("{}/{}{}", base, dir, slash))
+}
+
+///
+/// 解析 sources.list 中的一行，返回非安全更新仓库的地址及其在行中的位置
+///
+fn parse_sources_line(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let rest = trimmed
+        .strip_prefix("deb-src")
+        .or_else(|| trimmed.strip_prefix("deb"))?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    // 跳过 [arch=amd64 signed-by=...] 选项
+    let rest = rest.trim_start();
+    let rest = if rest.starts_with('[') {
+        rest[rest.find(']')? + 1..].trim_start()
+    } else {
+        rest
+    };
+    let mut fields = rest.split_whitespace();
+    let uri = fields.next()?;
+    let suite = fields.next()?;
+    if is_security(suite) {
+        return None;
+    }
+    Some((line.len() - rest.len(), uri))
+}
+
+///
+/// 替换 sources.list 格式配置中的仓库地址
+///
+fn replace_sources_list(content: &str, base: Option<&str>) -> String {
+    let mut new_content = String::new();
+    for line in content.lines() {
+        match parse_sources_line(line)
+            .and_then(|(start, uri)| Some((start, uri, replace_uri(uri, base)?)))
+        {
+            Some((start, uri, new_uri)) => {
+                new_content.push_str(&line[..start]);
+                new_content.push_str(&new_uri);
+                new_content.push_str(&line[start + uri.len()..]);
+            }
+            None => new_content.push_str(line),
+        }
+        new_content.push('\n');
+    }
+    new_content
+}
+
+///
+/// 获取 deb822 格式配置中一段配置的非安全更新仓库地址
+///
+fn deb822_uris(stanza: &str) -> Vec<&str> {
+    let field = |name: &str| {
+        stanza.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then_some(value)
+        })
+    };
+    let security = field("Suites")
+        .map(|suites| suites.split_whitespace().all(is_security))
+        .unwrap_or_default();
+    if security {
+        return vec![];
+    }
+    field("URIs")
+        .map(|uris| uris.split_whitespace().collect())
+        .unwrap_or_default()
+}
+
+///
+/// 替换 deb822 格式配置中的仓库地址，保留 Components、Signed-By 等其他字段
+///
+fn replace_deb822(content: &str, base: Option<&str>) -> String {
+    content
+        .split("\n\n")
+        .map(|stanza| {
+            let uris = deb822_uris(stanza);
+            if uris.is_empty() {
+                return stanza.to_string();
+            }
+            stanza
+                .split('\n')
+                .map(|line| match line.split_once(':') {
+                    Some((key, value)) if key.trim().eq_ignore_ascii_case("URIs") => {
+                        let value = value
+                            .split_whitespace()
+                            .map(|uri| replace_uri(uri, base).unwrap_or(uri.to_string()))
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        format!("{}: {}", key, value)
+                    }
+                    _ => line.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 #[cfg(target_os = "linux")]
-static APT_HOME: &str = "";
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    static DEFAULT_APT_PROFILE: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+        let path = PathBuf::from("/etc/apt/sources.list");
+        vec![path]
+    });
+
+    ///
+    /// Ubuntu 24.04 起默认使用的 deb822 格式配置文件
+    ///
+    fn deb822_profiles() -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = fs::read_dir("/etc/apt/sources.list.d")
+            .map(|dir| {
+                dir.filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "sources"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    }
+
+    ///
+    /// 按给定规则改写配置文件，内容未发生变化时不写入
+    ///
+    fn rewrite_profile(path: PathBuf, f: impl Fn(&str) -> String) {
+        if let Ok((_, old)) = read_config(vec![path.clone()]) {
+            let new = f(&old);
+            if new != old {
+                let _ = write_config(vec![path], &new);
+            }
+        }
+    }
+
+    impl AptMirror {
+        ///
+        /// 不存在任何配置文件时，根据发行版代号生成 sources.list
+        ///
+        fn template(&self, release: &OsRelease) -> String {
+            let (dir, components, security) = if release.id == "debian" {
+                (
+                    "debian",
+                    "main contrib non-free non-free-firmware",
+                    "http://security.debian.org/debian-security",
+                )
+            } else if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+                (
+                    "ubuntu",
+                    "main restricted universe multiverse",
+                    "http://security.ubuntu.com/ubuntu",
+                )
+            } else {
+                (
+                    "ubuntu-ports",
+                    "main restricted universe multiverse",
+                    "http://ports.ubuntu.com/ubuntu-ports",
+                )
+            };
+            format!(
+                include_str!("../../../templates/sources.list"),
+                format!("{}/{}", self.url.trim_end_matches('/'), dir),
+                release.codename,
+                components,
+                security
+            )
+        }
+    }
+
+    impl Reader for AptMirror {
+        fn new_config(&self) -> Result<String> {
+            match read_config(DEFAULT_APT_PROFILE.to_vec()) {
+                Ok((_, old)) => Ok(replace_sources_list(&old, Some(&self.url))),
+                Err(_) if deb822_profiles().is_empty() => {
+                    match fs::read_to_string("/etc/os-release")
+                        .ok()
+                        .and_then(|content| OsRelease::parse(&content))
+                    {
+                        Some(release) => Ok(self.template(&release)),
+                        None => bail!("Not a Debian based distribution"),
+                    }
+                }
+                Err(e) => Err(e),
+            }
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct AptPackageManager {}
+
+    impl MirrorConfigurate for AptPackageManager {
+        type R = AptMirror;
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![arg!(-u --url <URL>).help("mirror url").required(true)]
+        }
+
+        fn name(&self) -> &'static str {
+            "apt"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            let mut uris = vec![];
+            if let Ok((_, content)) = read_config(DEFAULT_APT_PROFILE.to_vec()) {
+                uris.extend(
+                    content
+                        .lines()
+                        .filter_map(parse_sources_line)
+                        .map(|(_, uri)| uri.to_string()),
+                );
+            }
+            for path in deb822_profiles() {
+                if let Ok(content) = fs::read_to_string(path) {
+                    uris.extend(
+                        content
+                            .split("\n\n")
+                            .flat_map(deb822_uris)
+                            .map(String::from),
+                    );
+                }
+            }
+            uris.iter()
+                .find_map(|uri| split_uri(uri))
+                .map(|(base, _)| AptMirror::new(base))
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            let mirrors = include_str!("../../../mirrors/apt.json");
+            let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+            mirrors
+                .into_iter()
+                .map(|x| {
+                    let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                        delay as i128
+                    } else {
+                        -1
+                    };
+                    Self::R { url_delay, ..x }
+                })
+                .collect()
+        }
+
+        fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+            let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+            let mirror = AptMirror::new(url);
+            self.set_mirror(mirror);
+        }
+
+        fn set_mirror(&self, mirror: Self::R) {
+            match read_config(self.get_default_profile_vec()) {
+                Ok((path, _)) => {
+                    rewrite_profile(path, |old| replace_sources_list(old, Some(&mirror.url)))
+                }
+                Err(_) => {
+                    if let Ok(new_config) = mirror.new_config() {
+                        let _ = write_config(self.get_default_profile_vec(), &new_config);
+                    }
+                }
+            }
+            for path in deb822_profiles() {
+                rewrite_profile(path, |old| replace_deb822(old, Some(&mirror.url)));
+            }
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            DEFAULT_APT_PROFILE.to_vec()
+        }
+
+        fn remove_mirror(&self, mirror: Self::R) {
+            if self
+                .current_mirror()
+                .is_some_and(|current| current.url == mirror.url)
+            {
+                self.reset_mirrors();
+            }
+        }
+
+        fn reset_mirrors(&self) {
+            for path in self.get_default_profile_vec() {
+                rewrite_profile(path, |old| replace_sources_list(old, None));
+            }
+            for path in deb822_profiles() {
+                rewrite_profile(path, |old| replace_deb822(old, None));
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    impl Reader for AptMirror {
+        fn new_config(&self) -> Result<String> {
+            unimplemented!("not support new_config for this platform")
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct AptPackageManager {}
+
+    impl MirrorConfigurate for AptPackageManager {
+        type R = AptMirror;
+
+        fn support(&self) -> bool {
+            false
+        }
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![arg!(-u --url <URL>).help("mirror url").required(true)]
+        }
+
+        fn name(&self) -> &'static str {
+            "apt"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            unimplemented!("not support current_mirror for this platform")
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            unimplemented!("not support get_mirrors for this platform")
+        }
+
+        fn set_mirror_by_args(&self, _args: &clap::ArgMatches) {
+            unimplemented!("not support set_mirror_by_args for this platform")
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            unimplemented!("not support get_default_profile_vec for this platform")
+        }
+
+        fn remove_mirror(&self, _mirror: Self::R) {
+            unimplemented!("not support remove_mirror for this platform")
+        }
+
+        fn reset_mirrors(&self) {
+            unimplemented!("not support reset_mirrors for this platform")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_one_line_style() {
+        let sources = r#"deb [arch=amd64 signed-by=/usr/share/keyrings/ubuntu-archive-keyring.gpg] http://archive.ubuntu.com/ubuntu/ jammy main restricted
+deb http://archive.ubuntu.com/ubuntu/ jammy-updates main restricted universe
+# deb-src http://archive.ubuntu.com/ubuntu/ jammy main restricted
+deb http://security.ubuntu.com/ubuntu/ jammy-security main restricted
+deb [signed-by=/etc/apt/keyrings/docker.gpg] https://download.docker.com/linux/ubuntu jammy stable"#;
+        let new_sources = replace_sources_list(sources, Some("https://mirrors.ustc.edu.cn"));
+        assert_eq!(
+            new_sources,
+            r#"deb [arch=amd64 signed-by=/usr/share/keyrings/ubuntu-archive-keyring.gpg] https://mirrors.ustc.edu.cn/ubuntu/ jammy main restricted
+deb https://mirrors.ustc.edu.cn/ubuntu/ jammy-updates main restricted universe
+# deb-src http://archive.ubuntu.com/ubuntu/ jammy main restricted
+deb http://security.ubuntu.com/ubuntu/ jammy-security main restricted
+deb [signed-by=/etc/apt/keyrings/docker.gpg] https://download.docker.com/linux/ubuntu jammy stable
+"#
+        );
+        assert_eq!(
+            replace_sources_list(&new_sources, None),
+            format!("{}\n", sources)
+        );
+    }
+
+    #[test]
+    fn replace_deb822_style() {
+        let sources = r#"Types: deb
+URIs: http://archive.ubuntu.com/ubuntu/
+Suites: noble noble-updates noble-backports
+Components: main restricted universe multiverse
+Signed-By: /usr/share/keyrings/ubuntu-archive-keyring.gpg
+
+Types: deb
+URIs: http://security.ubuntu.com/ubuntu/
+Suites: noble-security
+Components: main restricted universe multiverse
+Signed-By: /usr/share/keyrings/ubuntu-archive-keyring.gpg
+"#;
+        let new_sources = replace_deb822(sources, Some("https://mirrors.tuna.tsinghua.edu.cn"));
+        assert_eq!(
+            new_sources,
+            sources.replacen(
+                "http://archive.ubuntu.com",
+                "https://mirrors.tuna.tsinghua.edu.cn",
+                1
+            )
+        );
+        assert_eq!(replace_deb822(&new_sources, None), sources);
+    }
+
+    #[test]
+    fn parse_os_release() {
+        let release = OsRelease::parse(
+            "NAME=\"Linux Mint\"\nID=linuxmint\nID_LIKE=\"ubuntu debian\"\nVERSION_CODENAME=wilma\nUBUNTU_CODENAME=noble\n",
+        );
+        assert_eq!(
+            release,
+            Some(OsRelease {
+                id: "ubuntu".into(),
+                codename: "noble".into()
+            })
+        );
+    }
+}
@@ -0,0 +1,233 @@
+mod object;
+
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use object::{registry_url, BunConfig};
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use toml::Value;
+
+use super::{MirrorConfigurate, Reader};
+use std::{collections::HashMap, env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+const ENV_NAME: &str = "XDG_CONFIG_HOME";
+
+static DEFAULT_BUN_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let mut profiles = vec![];
+    if let Ok(value) = env::var(ENV_NAME) {
+        profiles.push(PathBuf::from(value).join(".bunfig.toml"));
+    }
+    profiles.push(dirs::home_dir().unwrap().join(".bunfig.toml"));
+    profiles
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct BunMirror {
+    url: String,
+    /// 作用域对应的 registry，如 myorg = "https://npm.example.com"
+    #[serde(default)]
+    scopes: HashMap<String, String>,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl BunMirror {
+    pub(crate) fn new(url: String, scopes: HashMap<String, String>) -> Self {
+        Self {
+            url,
+            scopes,
+            url_delay: -1,
+        }
+    }
+}
+
+impl Display for BunMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for BunMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        let scopes = value["scopes"]
+            .as_object()
+            .map(|scopes| {
+                scopes
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self::new(url.unwrap_or_default().to_string(), scopes)
+    }
+}
+
+impl Reader for BunMirror {
+    fn new_config(&self) -> Result<String> {
+        let mut config = match read_config(DEFAULT_BUN_PROFILES.to_vec()) {
+            Ok((_, toml)) => toml::from_str::<BunConfig>(&toml)?,
+            Err(_) => BunConfig::default(),
+        };
+        config.install.registry = Some(Value::String(self.url.clone()));
+        for (scope, url) in &self.scopes {
+            config
+                .install
+                .scopes
+                .insert(scope.clone(), Value::String(url.clone()));
+        }
+        Ok(toml::to_string(&config)?)
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct BunPackageManager {}
+
+impl MirrorConfigurate for BunPackageManager {
+    type R = BunMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(-u --url <URL>).help("mirror url").required(true),
+            arg!(-s --scope <SCOPE_URL>)
+                .help("scoped registry, e.g. myorg=https://npm.example.com")
+                .num_args(1..)
+                .action(clap::ArgAction::Append)
+                .required(false),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "bun"
+    }
+
+    fn current_mirror(&self) -> Option<BunMirror> {
+        if let Ok((_, toml)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(config) = toml::from_str::<BunConfig>(&toml) {
+                let url = registry_url(config.install.registry.as_ref()?)?;
+                let scopes = config
+                    .install
+                    .scopes
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.clone(), registry_url(v)?.to_string())))
+                    .collect();
+                return Some(BunMirror::new(url.to_string(), scopes));
+            }
+        }
+        None
+    }
+
+    fn get_mirrors(&self) -> Vec<BunMirror> {
+        let mirrors = include_str!("../../../mirrors/npm.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let scopes = args
+            .get_many::<String>("scope")
+            .unwrap_or_default()
+            .filter_map(|scope| {
+                let (name, url) = scope.split_once('=')?;
+                Some((name.trim().to_string(), url.trim().to_string()))
+            })
+            .collect();
+        let mirror = BunMirror::new(url, scopes);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: BunMirror) {
+        if let Ok((_, toml)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(mut old) = toml::from_str::<BunConfig>(&toml) {
+                if old.install.registry.as_ref().and_then(registry_url) == Some(&mirror.url) {
+                    old.install.registry = None;
+                }
+                old.install
+                    .scopes
+                    .retain(|_, v| registry_url(v) != Some(&mirror.url));
+                let toml = toml::to_string(&old).unwrap();
+                let _ = write_config(self.get_default_profile_vec(), &toml);
+            }
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, toml)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(mut old) = toml::from_str::<BunConfig>(&toml) {
+                // 只移除指向内置镜像的作用域，保留私有 registry
+                let mirrors: Vec<BunMirror> =
+                    serde_json::from_str(include_str!("../../../mirrors/npm.json"))
+                        .unwrap_or_default();
+                old.install.registry = None;
+                old.install.scopes.retain(|_, v| {
+                    !mirrors
+                        .iter()
+                        .any(|m| registry_url(v) == Some(m.url.as_str()))
+                });
+                let toml = toml::to_string(&old).unwrap();
+                let _ = write_config(self.get_default_profile_vec(), &toml);
+            }
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_BUN_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object::BunConfig;
+
+    use super::*;
+
+    #[test]
+    fn resolve_bun_config() {
+        let bunfig_text = r#"
+telemetry = false
+
+[install]
+registry = "https://registry.npmjs.org"
+exact = true
+
+[install.scopes]
+myorg = { url = "https://npm.example.com", token = "$NPM_TOKEN" }
+"#;
+        let bunfig: BunConfig = toml::from_str(bunfig_text).unwrap();
+        println!("bunfig: \n{:#?}", bunfig);
+        assert_eq!(
+            bunfig.install.registry.as_ref().and_then(registry_url),
+            Some("https://registry.npmjs.org")
+        );
+        assert_eq!(
+            bunfig.install.scopes.get("myorg").and_then(registry_url),
+            Some("https://npm.example.com")
+        );
+
+        let bunfig_text = toml::to_string(&bunfig).unwrap();
+        println!("bunfig_text: \n{}", bunfig_text);
+    }
+
+    #[test]
+    fn test_gen() {
+        let mirror = BunMirror::new("https://registry.npmmirror.com".into(), HashMap::new());
+        let config = mirror.new_config().unwrap();
+        println!("{}", config);
+    }
+}
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use toml::Value;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct BunConfig {
+    #[serde(default, skip_serializing_if = "Install::is_empty")]
+    pub(super) install: Install,
+    #[serde(flatten)]
+    extra_fields: HashMap<String, Value>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct Install {
+    /// 字符串或 { url, token } 形式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) registry: Option<Value>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub(super) scopes: Scopes,
+    #[serde(flatten)]
+    extra_fields: HashMap<String, Value>,
+}
+
+impl Install {
+    fn is_empty(&self) -> bool {
+        self.registry.is_none() && self.scopes.is_empty() && self.extra_fields.is_empty()
+    }
+}
+
+pub(super) type Scopes = HashMap<String, Value>;
+
+///
+/// 获取 registry 配置中的地址
+///
+pub(super) fn registry_url(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(url) => Some(url),
+        Value::Table(table) => table.get("url").and_then(Value::as_str),
+        _ => None,
+    }
+}
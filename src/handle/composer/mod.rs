@@ -0,0 +1,352 @@
+mod object;
+
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::{bail, Result};
+use clap::arg;
+use object::ComposerConfig;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, ser::PrettyFormatter, Serializer, Value};
+
+use super::{MirrorConfigurate, Reader};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+const ENV_NAME: &str = "COMPOSER_HOME";
+
+/// 替换的仓库名称
+const PACKAGIST: &str = "packagist";
+
+/// 数组形式的配置中通过 {"packagist.org": false} 禁用官方仓库
+const PACKAGIST_ORG: &str = "packagist.org";
+
+static DEFAULT_COMPOSER_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let home = dirs::home_dir().unwrap();
+    let composer_home = match env::var(ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        // 旧版本使用的目录存在时优先使用
+        Err(_) if home.join(".composer").exists() => home.join(".composer"),
+        Err(_) if cfg!(target_os = "windows") => dirs::config_dir().unwrap().join("Composer"),
+        Err(_) if cfg!(target_os = "macos") => home.join(".composer"),
+        Err(_) => dirs::config_dir().unwrap().join("composer"),
+    };
+    vec![composer_home.join("config.json")]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct ComposerMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl ComposerMirror {
+    pub fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for ComposerMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for ComposerMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 按 Composer 的格式以四个空格缩进输出
+///
+fn to_json(config: &ComposerConfig) -> Result<String> {
+    let mut buf = vec![];
+    let mut serializer =
+        Serializer::with_formatter(&mut buf, PrettyFormatter::with_indent(b"    "));
+    config.serialize(&mut serializer)?;
+    Ok(String::from_utf8(buf)? + "\n")
+}
+
+fn is_packagist_disabled(repo: &Value) -> bool {
+    [PACKAGIST, PACKAGIST_ORG]
+        .iter()
+        .any(|key| repo.get(key) == Some(&Value::Bool(false)))
+}
+
+///
+/// 数组形式中由镜像生成的仓库，只按内置镜像地址与要设置的地址匹配，避免移除私有仓库
+///
+fn is_mirror_repo(repo: &Value, urls: &[String]) -> bool {
+    repo["type"] == "composer"
+        && repo["url"]
+            .as_str()
+            .is_some_and(|url| urls.iter().any(|u| u == url))
+}
+
+fn mirror_urls() -> Vec<String> {
+    let mirrors: Vec<ComposerMirror> =
+        serde_json::from_str(include_str!("../../../mirrors/composer.json")).unwrap_or_default();
+    mirrors.into_iter().map(|mirror| mirror.url).collect()
+}
+
+///
+/// 当前替换官方仓库的镜像地址
+///
+fn packagist_url(config: &ComposerConfig) -> Option<&str> {
+    match &config.repositories {
+        Value::Object(repos) => repos.get(PACKAGIST)?["url"].as_str(),
+        Value::Array(repos) if repos.iter().any(is_packagist_disabled) => {
+            repos.iter().find(|repo| repo["type"] == "composer")?["url"].as_str()
+        }
+        _ => None,
+    }
+}
+
+///
+/// 设置 repositories.packagist，url 为 None 时移除
+///
+/// 数组形式的配置中将镜像插入到最前面，并禁用官方仓库
+///
+fn packagist_config(json: &str, url: Option<&str>, mirror_urls: &[String]) -> Result<String> {
+    let mut config = if json.trim().is_empty() {
+        ComposerConfig::default()
+    } else {
+        serde_json::from_str::<ComposerConfig>(json)?
+    };
+    match &mut config.repositories {
+        Value::Array(repos) => {
+            let mut urls = mirror_urls.to_vec();
+            urls.extend(url.map(String::from));
+            let len = repos.len();
+            repos.retain(|repo| !is_mirror_repo(repo, &urls));
+            // 只在设置或移除镜像时处理官方仓库，保留用户自行禁用官方仓库的配置
+            if url.is_some() || repos.len() != len {
+                repos.retain(|repo| !is_packagist_disabled(repo));
+            }
+            if let Some(url) = url {
+                repos.insert(0, json!({ "type": "composer", "url": url }));
+                repos.push(json!({ PACKAGIST_ORG: false }));
+            }
+            if repos.is_empty() {
+                config.repositories = Value::Null;
+            }
+        }
+        Value::Object(repos) => {
+            match url {
+                Some(url) => {
+                    repos.insert(PACKAGIST.into(), json!({ "type": "composer", "url": url }));
+                }
+                None => {
+                    repos.remove(PACKAGIST);
+                }
+            }
+            if repos.is_empty() {
+                config.repositories = Value::Null;
+            }
+        }
+        Value::Null => {
+            if let Some(url) = url {
+                config.repositories = json!({ PACKAGIST: { "type": "composer", "url": url } });
+            }
+        }
+        _ => bail!("invalid repositories"),
+    }
+    to_json(&config)
+}
+
+impl Reader for ComposerMirror {
+    fn new_config(&self) -> Result<String> {
+        let json = read_config(DEFAULT_COMPOSER_PROFILES.to_vec())
+            .map(|(_, json)| json)
+            .unwrap_or_default();
+        packagist_config(&json, Some(&self.url), &mirror_urls())
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct ComposerPackageManager {}
+
+impl MirrorConfigurate for ComposerPackageManager {
+    type R = ComposerMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![arg!(-u --url <url>)
+            .help("The url of the packagist mirror")
+            .required(true)]
+    }
+
+    fn name(&self) -> &'static str {
+        "composer"
+    }
+
+    fn current_mirror(&self) -> Option<ComposerMirror> {
+        let (_, json) = read_config(self.get_default_profile_vec()).ok()?;
+        let config = serde_json::from_str::<ComposerConfig>(&json).ok()?;
+        packagist_url(&config).map(|url| ComposerMirror::new(url.to_string()))
+    }
+
+    fn get_mirrors(&self) -> Vec<ComposerMirror> {
+        let mirrors = include_str!("../../../mirrors/composer.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let mirror = ComposerMirror::new(url);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: ComposerMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.url == mirror.url)
+        {
+            // 自定义的镜像地址不在内置镜像中，一并传入以便从数组形式中移除
+            let mut urls = mirror_urls();
+            urls.push(mirror.url);
+            if let Ok((_, json)) = read_config(self.get_default_profile_vec()) {
+                if let Ok(new_json) = packagist_config(&json, None, &urls) {
+                    let _ = write_config(self.get_default_profile_vec(), &new_json);
+                }
+            }
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, json)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(new_json) = packagist_config(&json, None, &mirror_urls()) {
+                let _ = write_config(self.get_default_profile_vec(), &new_json);
+            }
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_COMPOSER_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packagist_config() {
+        let json = r#"{
+    "config": {
+        "process-timeout": 600
+    },
+    "repositories": {
+        "private": {
+            "type": "vcs",
+            "url": "https://git.example.com/lib.git"
+        }
+    }
+}"#;
+        let new_json = packagist_config(
+            json,
+            Some("https://mirrors.aliyun.com/composer/"),
+            &mirror_urls(),
+        )
+        .unwrap();
+        assert_eq!(
+            new_json,
+            r#"{
+    "repositories": {
+        "packagist": {
+            "type": "composer",
+            "url": "https://mirrors.aliyun.com/composer/"
+        },
+        "private": {
+            "type": "vcs",
+            "url": "https://git.example.com/lib.git"
+        }
+    },
+    "config": {
+        "process-timeout": 600
+    }
+}
+"#
+        );
+        assert_eq!(
+            packagist_config(
+                "{}",
+                Some("https://mirrors.aliyun.com/composer/"),
+                &mirror_urls()
+            )
+            .and_then(|json| packagist_config(&json, None, &mirror_urls()))
+            .unwrap(),
+            "{}\n"
+        );
+    }
+
+    #[test]
+    fn test_packagist_config_array() {
+        let json = r#"{
+    "repositories": [
+        {
+            "type": "vcs",
+            "url": "https://git.example.com/lib.git"
+        }
+    ]
+}"#;
+        let new_json = packagist_config(
+            json,
+            Some("https://mirrors.aliyun.com/composer/"),
+            &mirror_urls(),
+        )
+        .unwrap();
+        assert_eq!(
+            new_json,
+            r#"{
+    "repositories": [
+        {
+            "type": "composer",
+            "url": "https://mirrors.aliyun.com/composer/"
+        },
+        {
+            "type": "vcs",
+            "url": "https://git.example.com/lib.git"
+        },
+        {
+            "packagist.org": false
+        }
+    ]
+}
+"#
+        );
+        let config = serde_json::from_str::<ComposerConfig>(&new_json).unwrap();
+        assert_eq!(
+            packagist_url(&config),
+            Some("https://mirrors.aliyun.com/composer/")
+        );
+        let new_json = packagist_config(
+            &new_json,
+            Some("https://mirrors.cloud.tencent.com/composer/"),
+            &mirror_urls(),
+        )
+        .unwrap();
+        assert_eq!(new_json.matches("\"type\": \"composer\"").count(), 1);
+        assert_eq!(new_json.matches("packagist.org").count(), 1);
+        assert_eq!(
+            packagist_config(&new_json, None, &mirror_urls()).unwrap(),
+            format!("{}\n", json)
+        );
+    }
+}
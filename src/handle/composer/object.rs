@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+///
+/// Composer 全局配置 config.json
+///
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct ComposerConfig {
+    /// 可以是以名称为键的对象，也可以是数组
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub(super) repositories: Value,
+    #[serde(flatten)]
+    extra_fields: Map<String, Value>,
+}
@@ -0,0 +1,295 @@
+mod object;
+
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use object::CondaConfig;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use super::{MirrorConfigurate, Reader};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+const ENV_NAME: &str = "CONDARC";
+
+static DEFAULT_CONDA_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let profile_path = match env::var(ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => dirs::home_dir().unwrap().join(".condarc"),
+    };
+    vec![profile_path]
+});
+
+/// 镜像站 anaconda 目录下提供的默认频道
+const DEFAULT_CHANNELS: [&str; 3] = ["main", "r", "msys2"];
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct CondaMirror {
+    url: String,
+    /// 镜像站 cloud 目录下提供的第三方频道，如 conda-forge、pytorch
+    #[serde(default)]
+    custom_channels: Vec<String>,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl CondaMirror {
+    pub(crate) fn new(url: String, custom_channels: Vec<String>) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            custom_channels,
+            url_delay: -1,
+        }
+    }
+
+    fn default_channels(&self) -> Vec<String> {
+        DEFAULT_CHANNELS
+            .iter()
+            .map(|channel| format!("{}/pkgs/{}", self.url, channel))
+            .collect()
+    }
+
+    fn cloud_url(&self) -> String {
+        format!("{}/cloud", self.url)
+    }
+
+    ///
+    /// 从配置中解析当前镜像
+    ///
+    fn from_config(config: &CondaConfig) -> Option<Self> {
+        let url = config
+            .default_channels
+            .first()?
+            .trim_end_matches('/')
+            .strip_suffix("/pkgs/main")?
+            .to_string();
+        let mut mirror = Self::new(url, vec![]);
+        mirror.custom_channels = config
+            .custom_channels
+            .iter()
+            .filter(|(_, url)| url.trim_end_matches('/') == mirror.cloud_url())
+            .map(|(name, _)| name.clone())
+            .collect();
+        Some(mirror)
+    }
+}
+
+impl Display for CondaMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for CondaMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        let custom_channels = value["custom_channels"]
+            .as_array()
+            .map(|channels| {
+                channels
+                    .iter()
+                    .filter_map(|c| c.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self::new(url.unwrap_or_default().to_string(), custom_channels)
+    }
+}
+
+///
+/// 移除配置中指向当前镜像的频道
+///
+fn remove_current(config: &mut CondaConfig) {
+    if let Some(current) = CondaMirror::from_config(config) {
+        config
+            .custom_channels
+            .retain(|name, _| !current.custom_channels.contains(name));
+    }
+    config.default_channels.clear();
+}
+
+///
+/// 将新配置合并到原配置中，已有的配置项保持原有位置，新增的配置项追加到末尾
+///
+fn merge_mapping(old: &mut Mapping, new: Mapping) {
+    old.retain(|key, _| new.contains_key(key));
+    for (key, value) in new {
+        match (old.get_mut(&key), value) {
+            (Some(Value::Mapping(old)), Value::Mapping(new)) => merge_mapping(old, new),
+            (_, value) => {
+                old.insert(key, value);
+            }
+        }
+    }
+}
+
+///
+/// 按原配置的顺序输出修改后的配置
+///
+fn to_yaml(yaml: &str, config: &CondaConfig) -> Result<String> {
+    let mut old = serde_yaml::from_str::<Option<Mapping>>(yaml)?.unwrap_or_default();
+    if let Value::Mapping(new) = serde_yaml::to_value(config)? {
+        merge_mapping(&mut old, new);
+    }
+    Ok(serde_yaml::to_string(&old)?)
+}
+
+impl Reader for CondaMirror {
+    fn new_config(&self) -> Result<String> {
+        let yaml = read_config(DEFAULT_CONDA_PROFILES.to_vec())
+            .map(|(_, yaml)| yaml)
+            .unwrap_or_default();
+        let mut config = serde_yaml::from_str::<Option<CondaConfig>>(&yaml)?.unwrap_or_default();
+        remove_current(&mut config);
+        if config.channels.is_empty() {
+            config.channels.push("defaults".into());
+        }
+        config.default_channels = self.default_channels();
+        for channel in &self.custom_channels {
+            config
+                .custom_channels
+                .insert(channel.clone(), self.cloud_url());
+        }
+        config.show_channel_urls = Some(true);
+        to_yaml(&yaml, &config)
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct CondaPackageManager {}
+
+impl MirrorConfigurate for CondaPackageManager {
+    type R = CondaMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(-u --url <URL>)
+                .help("The anaconda root of the mirror, e.g. https://mirrors.tuna.tsinghua.edu.cn/anaconda")
+                .required(true),
+            arg!(-c --channel <CHANNEL>)
+                .help("Custom channels served by the mirror, e.g. conda-forge")
+                .num_args(1..)
+                .action(clap::ArgAction::Append)
+                .required(false),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "conda"
+    }
+
+    fn current_mirror(&self) -> Option<CondaMirror> {
+        if let Ok((_, yaml)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(Some(config)) = serde_yaml::from_str::<Option<CondaConfig>>(&yaml) {
+                return CondaMirror::from_config(&config);
+            }
+        }
+        None
+    }
+
+    fn get_mirrors(&self) -> Vec<CondaMirror> {
+        let mirrors = include_str!("../../../mirrors/conda.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let custom_channels = args
+            .get_many::<String>("channel")
+            .unwrap_or_default()
+            .cloned()
+            .collect();
+        let mirror = CondaMirror::new(url, custom_channels);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: CondaMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.url == mirror.url)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, yaml)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(Some(mut config)) = serde_yaml::from_str::<Option<CondaConfig>>(&yaml) {
+                remove_current(&mut config);
+                if let Ok(yaml) = to_yaml(&yaml, &config) {
+                    let _ = write_config(self.get_default_profile_vec(), &yaml);
+                }
+            }
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_CONDA_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_conda_config() {
+        let condarc = r#"
+ssl_verify: false
+channels:
+  - defaults
+  - conda-forge
+show_channel_urls: true
+default_channels:
+  - https://mirrors.tuna.tsinghua.edu.cn/anaconda/pkgs/main
+  - https://mirrors.tuna.tsinghua.edu.cn/anaconda/pkgs/r
+custom_channels:
+  conda-forge: https://mirrors.tuna.tsinghua.edu.cn/anaconda/cloud
+  private: https://conda.example.com
+"#;
+        let mut config: CondaConfig = serde_yaml::from_str(condarc).unwrap();
+        let mirror = CondaMirror::from_config(&config).unwrap();
+        assert_eq!(mirror.url, "https://mirrors.tuna.tsinghua.edu.cn/anaconda");
+        assert_eq!(mirror.custom_channels, vec!["conda-forge".to_string()]);
+
+        remove_current(&mut config);
+        let yaml = to_yaml(condarc, &config).unwrap();
+        assert_eq!(
+            yaml,
+            r#"ssl_verify: false
+channels:
+- defaults
+- conda-forge
+show_channel_urls: true
+custom_channels:
+  private: https://conda.example.com
+"#
+        );
+    }
+
+    #[test]
+    fn test_gen() {
+        let mirror = CondaMirror::new(
+            "https://mirrors.tuna.tsinghua.edu.cn/anaconda".into(),
+            vec!["conda-forge".into(), "pytorch".into()],
+        );
+        let config = mirror.new_config().unwrap();
+        println!("{}", config);
+    }
+}
@@ -0,0 +1,21 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct CondaConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) channels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) default_channels: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(super) custom_channels: CustomChannels,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) show_channel_urls: Option<bool>,
+    /// 其他配置，写回时合并到原配置中以保持原有顺序
+    #[serde(flatten)]
+    extra_fields: Mapping,
+}
+
+pub(super) type CustomChannels = BTreeMap<String, String>;
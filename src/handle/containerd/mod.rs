@@ -0,0 +1,328 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use crate::utils::net_utils::test_connection;
+use anyhow::{bail, Result};
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::file_utils::read_config;
+
+use super::{write_config, MirrorConfigurate, Reader};
+
+pub(crate) use os_specific::*;
+
+/// 默认配置镜像的上游仓库
+const DOCKER_HUB: &str = "docker.io";
+
+/// 标记由本工具生成的 hosts.toml，reset 时只删除带有该标记的目录
+const MARKER: &str = "# generated by mirrors";
+
+fn default_registry() -> String {
+    DOCKER_HUB.to_string()
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct ContainerdMirror {
+    url: String,
+    /// 镜像对应的上游仓库，如 docker.io、ghcr.io
+    #[serde(default = "default_registry")]
+    registry: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl ContainerdMirror {
+    pub(crate) fn new(url: String, registry: String) -> Self {
+        Self {
+            url,
+            registry,
+            url_delay: -1,
+        }
+    }
+}
+
+impl Display for ContainerdMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for ContainerdMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        let registry = value["registry"].as_str().unwrap_or(DOCKER_HUB);
+        Self::new(url.unwrap_or_default().to_string(), registry.to_string())
+    }
+}
+
+///
+/// 上游仓库的实际地址，docker.io 的地址为 registry-1.docker.io
+///
+fn upstream_server(registry: &str) -> String {
+    if registry == DOCKER_HUB {
+        "https://registry-1.docker.io".to_string()
+    } else {
+        format!("https://{}", registry)
+    }
+}
+
+///
+/// 生成 hosts.toml，镜像只用于拉取，失败时回退到上游仓库
+///
+fn hosts_toml(registry: &str, url: &str) -> String {
+    format!(
+        "{}\nserver = \"{}\"\n\n[host.\"{}\"]\n  capabilities = [\"pull\", \"resolve\"]\n",
+        MARKER,
+        upstream_server(registry),
+        url.trim_end_matches('/')
+    )
+}
+
+///
+/// 上游仓库名称会作为 certs.d 下的目录名，不能包含路径分隔符或 ..
+///
+fn check_registry(registry: &str) -> Result<String> {
+    if registry.is_empty() || registry.contains(['/', '\\']) || registry.contains("..") {
+        bail!("invalid registry: {}", registry);
+    }
+    Ok(registry.to_string())
+}
+
+fn is_generated(hosts: &str) -> bool {
+    hosts.starts_with(MARKER)
+}
+
+///
+/// hosts.toml 中的第一个镜像地址
+///
+fn host_url(hosts: &str) -> Option<&str> {
+    let start = hosts.find("[host.\"")? + "[host.\"".len();
+    let end = start + hosts[start..].find("\"]")?;
+    Some(&hosts[start..end])
+}
+
+#[cfg(target_os = "linux")]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    /// containerd 2.x 默认读取该目录，1.x 需要在 config.toml 中设置 config_path
+    static CERTS_DIR: LazyLock<PathBuf> =
+        LazyLock::new(|| PathBuf::from("/etc/containerd/certs.d"));
+
+    fn hosts_path(registry: &str) -> PathBuf {
+        CERTS_DIR.join(registry).join("hosts.toml")
+    }
+
+    ///
+    /// 删除生成的 hosts.toml，目录中还有其他文件（如证书）时保留目录
+    ///
+    fn remove_generated(path: PathBuf) {
+        if read_config(vec![path.clone()]).is_ok_and(|(_, hosts)| is_generated(&hosts)) {
+            let _ = std::fs::remove_file(&path);
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::remove_dir(dir);
+            }
+        }
+    }
+
+    impl Reader for ContainerdMirror {
+        fn new_config(&self) -> Result<String> {
+            check_registry(&self.registry)?;
+            if let Ok((path, hosts)) = read_config(vec![hosts_path(&self.registry)]) {
+                if !is_generated(&hosts) {
+                    bail!("{} is not generated by mirrors, skipped", path.display());
+                }
+            }
+            Ok(hosts_toml(&self.registry, &self.url))
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct ContainerdPackageManager {}
+
+    impl MirrorConfigurate for ContainerdPackageManager {
+        type R = ContainerdMirror;
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![
+                arg!(-u --url <URL>).help("mirror url").required(true),
+                arg!(-r --registry <REGISTRY>)
+                    .help("The upstream registry of the mirror")
+                    .value_parser(|registry: &str| check_registry(registry))
+                    .default_value(DOCKER_HUB),
+            ]
+        }
+
+        fn name(&self) -> &'static str {
+            "containerd"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            let (_, hosts) = read_config(self.get_default_profile_vec()).ok()?;
+            host_url(&hosts).map(|url| ContainerdMirror::new(url.to_string(), default_registry()))
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            let mirrors = include_str!("../../../mirrors/docker.json");
+            let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+            mirrors
+                .into_iter()
+                .map(|x| {
+                    let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                        delay as i128
+                    } else {
+                        -1
+                    };
+                    Self::R { url_delay, ..x }
+                })
+                .collect()
+        }
+
+        fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+            let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+            let registry = args
+                .get_one::<String>("registry")
+                .cloned()
+                .unwrap_or_else(default_registry);
+            let mirror = ContainerdMirror::new(url, registry);
+            self.set_mirror(mirror);
+        }
+
+        fn set_mirror(&self, mirror: Self::R) {
+            // 不覆盖手动编写的 hosts.toml，需要提示用户未生效
+            match mirror.new_config() {
+                Ok(new_config) => {
+                    let _ = write_config(vec![hosts_path(&mirror.registry)], &new_config);
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            vec![hosts_path(DOCKER_HUB)]
+        }
+
+        fn remove_mirror(&self, mirror: Self::R) {
+            if check_registry(&mirror.registry).is_err() {
+                return;
+            }
+            let path = hosts_path(&mirror.registry);
+            if read_config(vec![path.clone()])
+                .is_ok_and(|(_, hosts)| host_url(&hosts) == Some(mirror.url.trim_end_matches('/')))
+            {
+                remove_generated(path);
+            }
+        }
+
+        fn reset_mirrors(&self) {
+            if let Ok(entries) = std::fs::read_dir(CERTS_DIR.as_path()) {
+                for entry in entries.flatten() {
+                    remove_generated(entry.path().join("hosts.toml"));
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_hosts_toml() {
+            let hosts = hosts_toml(DOCKER_HUB, "https://docker.m.daocloud.io/");
+            assert_eq!(
+                hosts,
+                "# generated by mirrors\nserver = \"https://registry-1.docker.io\"\n\n[host.\"https://docker.m.daocloud.io\"]\n  capabilities = [\"pull\", \"resolve\"]\n"
+            );
+            assert!(is_generated(&hosts));
+            assert_eq!(host_url(&hosts), Some("https://docker.m.daocloud.io"));
+            assert!(toml::from_str::<toml::Table>(&hosts).is_ok());
+
+            let hosts = hosts_toml("ghcr.io", "https://ghcr.m.daocloud.io");
+            assert!(hosts.contains("server = \"https://ghcr.io\""));
+            assert!(!is_generated("server = \"https://registry-1.docker.io\"\n"));
+        }
+
+        #[test]
+        fn test_check_registry() {
+            assert!(check_registry("docker.io").is_ok());
+            assert!(check_registry("registry.example.com:5000").is_ok());
+            assert!(check_registry("").is_err());
+            assert!(check_registry("../../etc").is_err());
+            assert!(check_registry("docker.io/library").is_err());
+            assert!(check_registry("..").is_err());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    impl Reader for ContainerdMirror {
+        fn new_config(&self) -> Result<String> {
+            unimplemented!("not support new_config for this platform")
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct ContainerdPackageManager {}
+
+    impl MirrorConfigurate for ContainerdPackageManager {
+        type R = ContainerdMirror;
+
+        fn support(&self) -> bool {
+            false
+        }
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![
+                arg!(-u --url <URL>).help("mirror url").required(true),
+                arg!(-r --registry <REGISTRY>)
+                    .help("The upstream registry of the mirror")
+                    .value_parser(|registry: &str| check_registry(registry))
+                    .default_value(DOCKER_HUB),
+            ]
+        }
+
+        fn name(&self) -> &'static str {
+            "containerd"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            unimplemented!("not support current_mirror for this platform")
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            unimplemented!("not support get_mirrors for this platform")
+        }
+
+        fn set_mirror_by_args(&self, _args: &clap::ArgMatches) {
+            unimplemented!("not support set_mirror_by_args for this platform")
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            unimplemented!("not support get_default_profile_vec for this platform")
+        }
+
+        fn remove_mirror(&self, _mirror: Self::R) {
+            unimplemented!("not support remove_mirror for this platform")
+        }
+
+        fn reset_mirrors(&self) {
+            unimplemented!("not support reset_mirrors for this platform")
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::{anyhow, Result};
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::PathBuf, sync::LazyLock};
+
+use super::{MirrorConfigurate, Reader};
+
+/// CPAN 官方源
+const DEFAULT_URL: &str = "http://www.cpan.org/";
+
+/// 新版本 cpan 在 ~/.cpan 不存在时使用 ~/.local/share/.cpan
+static DEFAULT_CPAN_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let home = dirs::home_dir().unwrap();
+    vec![
+        home.join(".cpan").join("CPAN").join("MyConfig.pm"),
+        home.join(".local")
+            .join("share")
+            .join(".cpan")
+            .join("CPAN")
+            .join("MyConfig.pm"),
+    ]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct CpanMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl CpanMirror {
+    pub fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for CpanMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for CpanMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 解析 urllist 数组，返回方括号内内容的起止位置及其中的地址
+///
+fn parse_urllist(config: &str) -> Option<(usize, usize, Vec<String>)> {
+    let key = config.find("'urllist'")?;
+    let start = key + config[key..].find('[')? + 1;
+    let mut urls = vec![];
+    let mut index = start;
+    loop {
+        let rest = &config[index..];
+        let item = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        index += rest.len() - item.len();
+        if item.starts_with(']') {
+            return Some((start, index, urls));
+        }
+        // 地址可能为 q[...]、'...' 或 "..."
+        let (url, len) = if let Some(item) = item.strip_prefix("q[") {
+            let end = item.find(']')?;
+            (&item[..end], end + 3)
+        } else {
+            let quote = item.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+            let end = item[1..].find(quote)?;
+            (&item[1..end + 1], end + 2)
+        };
+        urls.push(url.to_string());
+        index += len;
+    }
+}
+
+///
+/// 替换 urllist 数组，不存在时插入到 $CPAN::Config 的开头
+///
+fn set_urllist(config: &str, urls: &[String]) -> Result<String> {
+    let list = urls
+        .iter()
+        .map(|url| format!("q[{}]", url))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut config = config.to_string();
+    match parse_urllist(&config) {
+        Some((start, end, _)) => config.replace_range(start..end, &list),
+        None => {
+            let start = config
+                .find("$CPAN::Config = {")
+                .and_then(|start| Some(start + config[start..].find('\n')? + 1))
+                .ok_or_else(|| anyhow!("$CPAN::Config not found"))?;
+            config.insert_str(start, &format!("  'urllist' => [{}],\n", list));
+        }
+    }
+    Ok(config)
+}
+
+impl Reader for CpanMirror {
+    fn new_config(&self) -> Result<String> {
+        // 配置需要通过 cpan 初始化生成
+        let (_, config) = read_config(DEFAULT_CPAN_PROFILES.to_vec())?;
+        let mut urls = parse_urllist(&config)
+            .map(|(_, _, urls)| urls)
+            .unwrap_or_default();
+        urls.retain(|url| url != &self.url);
+        urls.insert(0, self.url.clone());
+        set_urllist(&config, &urls)
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct CpanPackageManager {}
+
+impl MirrorConfigurate for CpanPackageManager {
+    type R = CpanMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![arg!(-u --url <url>)
+            .help("The url of the CPAN mirror")
+            .required(true)]
+    }
+
+    fn name(&self) -> &'static str {
+        "cpan"
+    }
+
+    fn current_mirror(&self) -> Option<CpanMirror> {
+        let (_, config) = read_config(self.get_default_profile_vec()).ok()?;
+        let (_, _, urls) = parse_urllist(&config)?;
+        urls.into_iter().next().map(CpanMirror::new)
+    }
+
+    fn get_mirrors(&self) -> Vec<CpanMirror> {
+        let mirrors = include_str!("../../../mirrors/cpan.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let mirror = CpanMirror::new(url);
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: CpanMirror) {
+        // 写回读取到的配置文件
+        if let (Ok((path, _)), Ok(new_config)) = (
+            read_config(self.get_default_profile_vec()),
+            mirror.new_config(),
+        ) {
+            let _ = write_config(vec![path], &new_config);
+        }
+    }
+
+    fn remove_mirror(&self, mirror: CpanMirror) {
+        if let Ok((path, config)) = read_config(self.get_default_profile_vec()) {
+            if let Some((_, _, mut urls)) = parse_urllist(&config) {
+                urls.retain(|url| url != &mirror.url);
+                if let Ok(new_config) = set_urllist(&config, &urls) {
+                    let _ = write_config(vec![path], &new_config);
+                }
+            }
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((path, config)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(new_config) = set_urllist(&config, &[DEFAULT_URL.to_string()]) {
+                let _ = write_config(vec![path], &new_config);
+            }
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_CPAN_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urllist() {
+        let config = r#"
+$CPAN::Config = {
+  'auto_commit' => q[0],
+  'urllist' => [
+    q[http://www.cpan.org/],
+    'https://cpan.metacpan.org/',
+  ],
+  'use_sqlite' => q[0],
+};
+1;
+__END__
+"#;
+        let (_, _, urls) = parse_urllist(config).unwrap();
+        assert_eq!(
+            urls,
+            vec!["http://www.cpan.org/", "https://cpan.metacpan.org/"]
+        );
+        let new_config = set_urllist(
+            config,
+            &["https://mirrors.tuna.tsinghua.edu.cn/CPAN/".to_string()],
+        )
+        .unwrap();
+        assert_eq!(
+            new_config,
+            r#"
+$CPAN::Config = {
+  'auto_commit' => q[0],
+  'urllist' => [q[https://mirrors.tuna.tsinghua.edu.cn/CPAN/]],
+  'use_sqlite' => q[0],
+};
+1;
+__END__
+"#
+        );
+        let config = "$CPAN::Config = {\n  'auto_commit' => q[0],\n};\n";
+        assert_eq!(
+            set_urllist(config, &[DEFAULT_URL.to_string()]).unwrap(),
+            "$CPAN::Config = {\n  'urllist' => [q[http://www.cpan.org/]],\n  'auto_commit' => q[0],\n};\n"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+    profile_utils::{get_block, replace_block},
+};
+use anyhow::Result;
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+use super::{MirrorConfigurate, Reader};
+
+const ENV_NAME: &str = "R_PROFILE_USER";
+
+/// .Rprofile 中配置块的名称
+const BLOCK_NAME: &str = "cran";
+
+static DEFAULT_CRAN_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let profile_path = match env::var(ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => dirs::home_dir().unwrap().join(".Rprofile"),
+    };
+    vec![profile_path]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct CranMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl CranMirror {
+    pub fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for CranMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for CranMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 从配置块中解析 CRAN 地址
+///
+fn parse_repos(block: &str) -> Option<&str> {
+    let (_, rest) = block.split_once("CRAN")?;
+    let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    rest[1..].split(quote).next()
+}
+
+impl Reader for CranMirror {
+    fn new_config(&self) -> Result<String> {
+        let profile = read_config(DEFAULT_CRAN_PROFILES.to_vec())
+            .map(|(_, profile)| profile)
+            .unwrap_or_default();
+        let block = format!("options(repos = c(CRAN = \"{}\"))", self.url);
+        replace_block(&profile, BLOCK_NAME, Some(&block))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct CranPackageManager {}
+
+impl MirrorConfigurate for CranPackageManager {
+    type R = CranMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![arg!(-u --url <url>)
+            .help("The url of the CRAN mirror")
+            .required(true)]
+    }
+
+    fn name(&self) -> &'static str {
+        "cran"
+    }
+
+    fn current_mirror(&self) -> Option<CranMirror> {
+        let (_, profile) = read_config(self.get_default_profile_vec()).ok()?;
+        let block = get_block(&profile, BLOCK_NAME)?;
+        parse_repos(&block).map(|url| CranMirror::new(url.to_string()))
+    }
+
+    fn get_mirrors(&self) -> Vec<CranMirror> {
+        let mirrors = include_str!("../../../mirrors/cran.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let mirror = CranMirror::new(url);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: CranMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.url == mirror.url)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, profile)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(new_profile) = replace_block(&profile, BLOCK_NAME, None) {
+                if new_profile != profile {
+                    let _ = write_config(self.get_default_profile_vec(), &new_profile);
+                }
+            }
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_CRAN_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repos() {
+        let profile = "local({\n  Sys.setenv(LANG = \"en\")\n})\n";
+        let block = "options(repos = c(CRAN = \"https://mirrors.tuna.tsinghua.edu.cn/CRAN/\"))";
+        let new_profile = replace_block(profile, BLOCK_NAME, Some(block)).unwrap();
+        assert_eq!(
+            parse_repos(&get_block(&new_profile, BLOCK_NAME).unwrap()),
+            Some("https://mirrors.tuna.tsinghua.edu.cn/CRAN/")
+        );
+        assert_eq!(
+            parse_repos("options(repos = c(CRAN='https://cloud.r-project.org/'))"),
+            Some("https://cloud.r-project.org/")
+        );
+        assert_eq!(
+            replace_block(&new_profile, BLOCK_NAME, None).unwrap(),
+            profile
+        );
+    }
+}
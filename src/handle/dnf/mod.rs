@@ -0,0 +1,466 @@
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::PathBuf,
+};
+
+use crate::utils::net_utils::test_connection;
+use anyhow::{bail, Result};
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::utils::file_utils::read_config;
+
+use super::{write_config, MirrorConfigurate, Reader};
+
+pub(crate) use os_specific::*;
+
+///
+/// 发行版仓库在镜像站上的目录，以及官方的 baseurl 前缀和 metalink/mirrorlist 主机
+///
+struct Family {
+    dir: &'static str,
+    /// 第一个为重置时使用的地址
+    upstreams: &'static [&'static str],
+    mirrorlist_host: &'static str,
+}
+
+/// epel 与 fedora 共用 metalink 主机，需要排在 fedora 之前匹配
+///
+/// RHEL 的订阅仓库（cdn.redhat.com）需要证书认证，镜像站没有对应内容，不做修改
+const FAMILIES: [Family; 4] = [
+    Family {
+        dir: "epel",
+        upstreams: &[
+            "https://download.example/pub/epel",
+            "http://download.example/pub/epel",
+            "https://dl.fedoraproject.org/pub/epel",
+        ],
+        mirrorlist_host: "mirrors.fedoraproject.org",
+    },
+    Family {
+        dir: "fedora",
+        upstreams: &[
+            "http://download.example/pub/fedora/linux",
+            "https://download.example/pub/fedora/linux",
+            "https://dl.fedoraproject.org/pub/fedora/linux",
+        ],
+        mirrorlist_host: "mirrors.fedoraproject.org",
+    },
+    Family {
+        dir: "rocky",
+        upstreams: &[
+            "http://dl.rockylinux.org/$contentdir",
+            "https://dl.rockylinux.org/$contentdir",
+        ],
+        mirrorlist_host: "mirrors.rockylinux.org",
+    },
+    Family {
+        dir: "almalinux",
+        upstreams: &[
+            "https://repo.almalinux.org/almalinux",
+            "http://repo.almalinux.org/almalinux",
+        ],
+        mirrorlist_host: "mirrors.almalinux.org",
+    },
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct DnfMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl DnfMirror {
+    pub(crate) fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for DnfMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for DnfMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 解析 baseurl/metalink/mirrorlist 配置行（包括被注释的），返回是否被注释、键和值
+///
+/// .repo 文件按行编辑而不使用 rust-ini：rust-ini 写回时会丢弃注释，
+/// 而被注释的 baseurl/metalink 正是切换和恢复时需要保留的内容，同时也需要保持原有的顺序和格式
+///
+fn parse_option(line: &str) -> Option<(bool, String, &str)> {
+    let trimmed = line.trim();
+    let uncommented = trimmed.trim_start_matches('#').trim_start();
+    let (key, value) = uncommented.split_once('=')?;
+    let key = key.trim().to_lowercase();
+    if !["baseurl", "metalink", "mirrorlist"].contains(&key.as_str()) {
+        return None;
+    }
+    Some((uncommented.len() != trimmed.len(), key, value.trim()))
+}
+
+///
+/// 一个仓库段落中与镜像相关的配置
+///
+struct RepoUrls {
+    /// baseurl 所在行
+    baseurl: usize,
+    /// metalink/mirrorlist 所在行及是否被注释
+    mirrorlists: Vec<(usize, bool)>,
+    family: &'static Family,
+    /// baseurl 中 $releasever 等变量所在的相对路径
+    rest: String,
+    /// 当前使用的镜像站地址，使用官方地址时为 None
+    base: Option<String>,
+}
+
+///
+/// 定位段落中属于发行版官方仓库的 baseurl，第三方仓库（如 docker-ce、rpmfusion）返回 None
+///
+fn locate(lines: &[&str]) -> Option<RepoUrls> {
+    let options: Vec<(usize, bool, String, &str)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| parse_option(line).map(|(c, k, v)| (i, c, k, v)))
+        .collect();
+    // 优先使用未被注释的 baseurl
+    let (baseurl, _, _, value) = options
+        .iter()
+        .filter(|(_, _, key, _)| key == "baseurl")
+        .min_by_key(|(_, commented, _, _)| *commented)?;
+    let url = value.split_whitespace().next()?;
+    let mirrorlists: Vec<(usize, bool, &str)> = options
+        .iter()
+        .filter(|(_, _, key, _)| key != "baseurl")
+        .map(|(i, commented, _, value)| (*i, *commented, *value))
+        .collect();
+    for family in FAMILIES.iter() {
+        let repo_urls = |rest: &str, base: Option<&str>| RepoUrls {
+            baseurl: *baseurl,
+            mirrorlists: mirrorlists.iter().map(|(i, c, _)| (*i, *c)).collect(),
+            family,
+            rest: rest.trim_start_matches('/').to_string(),
+            base: base.map(String::from),
+        };
+        if let Some(rest) = family.upstreams.iter().find_map(|u| url.strip_prefix(u)) {
+            return Some(repo_urls(rest, None));
+        }
+        let official = mirrorlists.iter().any(|(_, _, value)| {
+            Url::parse(value)
+                .ok()
+                .is_some_and(|u| u.host_str() == Some(family.mirrorlist_host))
+        });
+        if official {
+            if let Some((base, rest)) = url.split_once(&format!("/{}/", family.dir)) {
+                return Some(repo_urls(rest, Some(base)));
+            }
+        }
+    }
+    None
+}
+
+///
+/// 按段落拆分 .repo 文件，第一段为第一个 [section] 之前的内容
+///
+fn split_sections(content: &str) -> Vec<Vec<&str>> {
+    let mut sections = vec![vec![]];
+    for line in content.lines() {
+        if line.trim_start().starts_with('[') {
+            sections.push(vec![]);
+        }
+        if let Some(section) = sections.last_mut() {
+            section.push(line);
+        }
+    }
+    sections
+}
+
+///
+/// 将官方仓库切换到镜像站的 baseurl 并注释掉 metalink/mirrorlist，
+/// base 为 None 时恢复原来的 metalink/mirrorlist
+///
+fn rewrite_repo(content: &str, base: Option<&str>) -> String {
+    let mut new_content = String::new();
+    for section in split_sections(content) {
+        let mut lines: Vec<String> = section.iter().map(|line| line.to_string()).collect();
+        if let Some(urls) = locate(&section) {
+            match base {
+                Some(base) => {
+                    lines[urls.baseurl] = format!(
+                        "baseurl={}/{}/{}",
+                        base.trim_end_matches('/'),
+                        urls.family.dir,
+                        urls.rest
+                    );
+                    for (i, commented) in urls.mirrorlists {
+                        if !commented {
+                            lines[i] = format!("#{}", section[i].trim_start());
+                        }
+                    }
+                }
+                None => {
+                    let baseurl = format!("baseurl={}/{}", urls.family.upstreams[0], urls.rest);
+                    if urls.mirrorlists.is_empty() {
+                        lines[urls.baseurl] = baseurl;
+                    } else {
+                        lines[urls.baseurl] = format!("#{}", baseurl);
+                        for (i, _) in urls.mirrorlists {
+                            lines[i] = section[i].trim_start_matches(['#', ' ']).to_string();
+                        }
+                    }
+                }
+            }
+        }
+        for line in lines {
+            new_content.push_str(&line);
+            new_content.push('\n');
+        }
+    }
+    new_content
+}
+
+#[cfg(target_os = "linux")]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    ///
+    /// /etc/yum.repos.d 下的所有仓库配置文件
+    ///
+    fn repo_profiles() -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = fs::read_dir("/etc/yum.repos.d")
+            .map(|dir| {
+                dir.filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "repo"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    }
+
+    ///
+    /// 改写所有仓库配置文件，内容未发生变化时不写入
+    ///
+    fn rewrite_profiles(base: Option<&str>) {
+        for path in repo_profiles() {
+            if let Ok((_, old)) = read_config(vec![path.clone()]) {
+                let new = rewrite_repo(&old, base);
+                if new != old {
+                    let _ = write_config(vec![path], &new);
+                }
+            }
+        }
+    }
+
+    impl Reader for DnfMirror {
+        fn new_config(&self) -> Result<String> {
+            match read_config(repo_profiles()) {
+                Ok((_, repo)) => Ok(rewrite_repo(&repo, Some(&self.url))),
+                Err(_) => bail!("Not a dnf based distribution"),
+            }
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct DnfPackageManager {}
+
+    impl MirrorConfigurate for DnfPackageManager {
+        type R = DnfMirror;
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![arg!(-u --url <URL>).help("mirror url").required(true)]
+        }
+
+        fn name(&self) -> &'static str {
+            "dnf"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            self.get_default_profile_vec().into_iter().find_map(|path| {
+                let repo = fs::read_to_string(path).ok()?;
+                split_sections(&repo)
+                    .iter()
+                    .find_map(|section| locate(section)?.base)
+                    .map(DnfMirror::new)
+            })
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            let mirrors = include_str!("../../../mirrors/dnf.json");
+            let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+            mirrors
+                .into_iter()
+                .map(|x| {
+                    let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                        delay as i128
+                    } else {
+                        -1
+                    };
+                    Self::R { url_delay, ..x }
+                })
+                .collect()
+        }
+
+        fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+            let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+            let mirror = DnfMirror::new(url);
+            self.set_mirror(mirror);
+        }
+
+        fn set_mirror(&self, mirror: Self::R) {
+            rewrite_profiles(Some(&mirror.url));
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            repo_profiles()
+        }
+
+        fn remove_mirror(&self, mirror: Self::R) {
+            if self
+                .current_mirror()
+                .is_some_and(|current| current.url == mirror.url)
+            {
+                self.reset_mirrors();
+            }
+        }
+
+        fn reset_mirrors(&self) {
+            rewrite_profiles(None);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    impl Reader for DnfMirror {
+        fn new_config(&self) -> Result<String> {
+            unimplemented!("not support new_config for this platform")
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct DnfPackageManager {}
+
+    impl MirrorConfigurate for DnfPackageManager {
+        type R = DnfMirror;
+
+        fn support(&self) -> bool {
+            false
+        }
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![arg!(-u --url <URL>).help("mirror url").required(true)]
+        }
+
+        fn name(&self) -> &'static str {
+            "dnf"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            unimplemented!("not support current_mirror for this platform")
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            unimplemented!("not support get_mirrors for this platform")
+        }
+
+        fn set_mirror_by_args(&self, _args: &clap::ArgMatches) {
+            unimplemented!("not support set_mirror_by_args for this platform")
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            unimplemented!("not support get_default_profile_vec for this platform")
+        }
+
+        fn remove_mirror(&self, _mirror: Self::R) {
+            unimplemented!("not support remove_mirror for this platform")
+        }
+
+        fn reset_mirrors(&self) {
+            unimplemented!("not support reset_mirrors for this platform")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switch_and_restore_metalink() {
+        let repo = r#"[fedora]
+name=Fedora $releasever - $basearch
+#baseurl=http://download.example/pub/fedora/linux/releases/$releasever/Everything/$basearch/os/
+metalink=https://mirrors.fedoraproject.org/metalink?repo=fedora-$releasever&arch=$basearch
+enabled=1
+gpgkey=file:///etc/pki/rpm-gpg/RPM-GPG-KEY-fedora-$releasever-$basearch
+
+[docker-ce-stable]
+name=Docker CE Stable - $basearch
+baseurl=https://download.docker.com/linux/fedora/$releasever/$basearch/stable
+gpgkey=https://download.docker.com/linux/fedora/gpg
+"#;
+        let mirrored = rewrite_repo(repo, Some("https://mirrors.tuna.tsinghua.edu.cn"));
+        assert_eq!(
+            mirrored,
+            r#"[fedora]
+name=Fedora $releasever - $basearch
+baseurl=https://mirrors.tuna.tsinghua.edu.cn/fedora/releases/$releasever/Everything/$basearch/os/
+#metalink=https://mirrors.fedoraproject.org/metalink?repo=fedora-$releasever&arch=$basearch
+enabled=1
+gpgkey=file:///etc/pki/rpm-gpg/RPM-GPG-KEY-fedora-$releasever-$basearch
+
+[docker-ce-stable]
+name=Docker CE Stable - $basearch
+baseurl=https://download.docker.com/linux/fedora/$releasever/$basearch/stable
+gpgkey=https://download.docker.com/linux/fedora/gpg
+"#
+        );
+        assert_eq!(
+            split_sections(&mirrored)
+                .iter()
+                .find_map(|section| locate(section)?.base),
+            Some("https://mirrors.tuna.tsinghua.edu.cn".to_string())
+        );
+        assert_eq!(rewrite_repo(&mirrored, None), repo);
+    }
+
+    #[test]
+    fn switch_rocky_mirrorlist() {
+        let repo = r#"[baseos]
+name=Rocky Linux $releasever - BaseOS
+mirrorlist=https://mirrors.rockylinux.org/mirrorlist?arch=$basearch&repo=BaseOS-$releasever$rltype
+#baseurl=http://dl.rockylinux.org/$contentdir/$releasever/BaseOS/$basearch/os/
+"#;
+        let mirrored = rewrite_repo(repo, Some("https://mirrors.ustc.edu.cn/"));
+        assert_eq!(
+            mirrored,
+            r#"[baseos]
+name=Rocky Linux $releasever - BaseOS
+#mirrorlist=https://mirrors.rockylinux.org/mirrorlist?arch=$basearch&repo=BaseOS-$releasever$rltype
+baseurl=https://mirrors.ustc.edu.cn/rocky/$releasever/BaseOS/$basearch/os/
+"#
+        );
+        assert_eq!(rewrite_repo(&mirrored, None), repo);
+    }
+}
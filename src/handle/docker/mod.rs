@@ -51,6 +51,8 @@ impl From<serde_json::Value> for DockerMirror {
 #[cfg(target_os = "linux")]
 mod os_specific {
 
+    use select_mirror_derive::SelectMirror;
+
     use super::*;
 
     static DEFAULT_DOCKER_PROFILE: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
@@ -0,0 +1,243 @@
+use crate::utils::{
+    net_utils::test_connection,
+    profile_utils::{read_env_block, remove_env_block, shell_profiles, write_env_block, Shell},
+};
+use anyhow::Result;
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf};
+
+use super::{MirrorConfigurate, Reader};
+
+/// shell 启动文件中配置块的名称
+const BLOCK_NAME: &str = "flutter";
+
+const PUB_HOSTED_URL: &str = "PUB_HOSTED_URL";
+
+const FLUTTER_STORAGE_BASE_URL: &str = "FLUTTER_STORAGE_BASE_URL";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct FlutterMirror {
+    #[serde(default)]
+    name: String,
+    pub_hosted_url: String,
+    storage_base_url: String,
+    /// 与配置块不一致的当前环境变量，如设置后尚未重新加载 shell 启动文件
+    #[serde(skip)]
+    env_values: Vec<String>,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl FlutterMirror {
+    pub(crate) fn new(name: String, pub_hosted_url: String, storage_base_url: String) -> Self {
+        Self {
+            name,
+            pub_hosted_url,
+            storage_base_url,
+            env_values: vec![],
+            url_delay: -1,
+        }
+    }
+
+    fn vars(&self) -> [(&str, &str); 2] {
+        [
+            (PUB_HOSTED_URL, self.pub_hosted_url.as_str()),
+            (FLUTTER_STORAGE_BASE_URL, self.storage_base_url.as_str()),
+        ]
+    }
+}
+
+impl Display for FlutterMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "{} {}ms", self.pub_hosted_url, self.url_delay)
+        } else {
+            write!(
+                f,
+                "{}: {} {}ms",
+                self.name, self.pub_hosted_url, self.url_delay
+            )
+        }
+    }
+}
+
+impl From<serde_json::Value> for FlutterMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let name = value["name"].as_str();
+        let pub_hosted_url = value["pub_hosted_url"].as_str();
+        let storage_base_url = value["storage_base_url"].as_str();
+        Self::new(
+            name.unwrap_or_default().to_string(),
+            pub_hosted_url.unwrap_or_default().to_string(),
+            storage_base_url.unwrap_or_default().to_string(),
+        )
+    }
+}
+
+///
+/// 合并配置块与环境变量，配置块优先，两者不一致时记录环境变量的值
+///
+fn current_values(
+    block: &[(String, String)],
+    env: impl Fn(&str) -> Option<String>,
+) -> Option<FlutterMirror> {
+    let mut env_values = vec![];
+    let mut get = |key: &str| {
+        let profile = block.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+        let env = env(key);
+        match (profile, env) {
+            (Some(profile), Some(env)) if profile != env => {
+                env_values.push(format!("{}={}", key, env));
+                Some(profile)
+            }
+            (profile, env) => profile.or(env),
+        }
+    };
+    let pub_hosted_url = get(PUB_HOSTED_URL);
+    let storage_base_url = get(FLUTTER_STORAGE_BASE_URL);
+    if pub_hosted_url.is_none() && storage_base_url.is_none() {
+        return None;
+    }
+    Some(FlutterMirror {
+        env_values,
+        ..FlutterMirror::new(
+            String::new(),
+            pub_hosted_url.unwrap_or_default(),
+            storage_base_url.unwrap_or_default(),
+        )
+    })
+}
+
+impl Reader for FlutterMirror {
+    ///
+    /// 写入 bash、zsh 启动文件的配置块内容
+    ///
+    fn new_config(&self) -> Result<String> {
+        Ok(Shell::Posix.render_env(&self.vars()))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct FlutterPackageManager {}
+
+impl MirrorConfigurate for FlutterPackageManager {
+    type R = FlutterMirror;
+
+    fn support(&self) -> bool {
+        !cfg!(target_os = "windows")
+    }
+
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(-p --pub <PUB_HOSTED_URL>)
+                .help("The url of PUB_HOSTED_URL, e.g. https://pub.flutter-io.cn")
+                .required(true),
+            arg!(-s --storage <FLUTTER_STORAGE_BASE_URL>)
+                .help("The url of FLUTTER_STORAGE_BASE_URL, e.g. https://storage.flutter-io.cn")
+                .required(true),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "flutter"
+    }
+
+    fn current_mirror(&self) -> Option<FlutterMirror> {
+        current_values(&read_env_block(BLOCK_NAME), |key| env::var(key).ok())
+    }
+
+    fn get_mirrors(&self) -> Vec<FlutterMirror> {
+        let mirrors = include_str!("../../../mirrors/flutter.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.pub_hosted_url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let pub_hosted_url = args.get_one::<String>("pub").cloned().unwrap_or_default();
+        let storage_base_url = args
+            .get_one::<String>("storage")
+            .cloned()
+            .unwrap_or_default();
+        let mirror = FlutterMirror::new(String::new(), pub_hosted_url, storage_base_url);
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: FlutterMirror) {
+        let _ = write_env_block(BLOCK_NAME, &mirror.vars());
+    }
+
+    fn remove_mirror(&self, mirror: FlutterMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.pub_hosted_url == mirror.pub_hosted_url)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        let _ = remove_env_block(BLOCK_NAME);
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        shell_profiles().into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen() {
+        let mirror = FlutterMirror::new(
+            "flutter-io.cn".into(),
+            "https://pub.flutter-io.cn".into(),
+            "https://storage.flutter-io.cn".into(),
+        );
+        assert_eq!(
+            mirror.new_config().unwrap(),
+            "export PUB_HOSTED_URL=\"https://pub.flutter-io.cn\"\nexport FLUTTER_STORAGE_BASE_URL=\"https://storage.flutter-io.cn\""
+        );
+    }
+
+    #[test]
+    fn test_current_values() {
+        let block = vec![(
+            PUB_HOSTED_URL.to_string(),
+            "https://pub.flutter-io.cn".to_string(),
+        )];
+        // 配置块优先，不一致的环境变量一并显示
+        let mirror = current_values(&block, |key| match key {
+            PUB_HOSTED_URL => Some("https://pub.dev".into()),
+            _ => Some("https://storage.flutter-io.cn".into()),
+        })
+        .unwrap();
+        assert_eq!(mirror.pub_hosted_url, "https://pub.flutter-io.cn");
+        assert_eq!(mirror.storage_base_url, "https://storage.flutter-io.cn");
+        assert_eq!(mirror.env_values, vec!["PUB_HOSTED_URL=https://pub.dev"]);
+
+        // 配置块不存在时读取环境变量
+        let mirror = current_values(&[], |key| {
+            (key == PUB_HOSTED_URL).then(|| "https://pub.flutter-io.cn".into())
+        })
+        .unwrap();
+        assert_eq!(mirror.pub_hosted_url, "https://pub.flutter-io.cn");
+        assert!(mirror.env_values.is_empty());
+        assert!(current_values(&[], |_| None).is_none());
+    }
+}
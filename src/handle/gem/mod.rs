@@ -0,0 +1,251 @@
+mod object;
+
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use object::{BundleConfig, GemrcConfig};
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+
+use super::{MirrorConfigurate, Reader};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+const ENV_NAME: &str = "GEMRC";
+
+const BUNDLE_ENV_NAME: &str = "BUNDLE_USER_CONFIG";
+
+static DEFAULT_GEM_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let profile_path = match env::var(ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => dirs::home_dir().unwrap().join(".gemrc"),
+    };
+    vec![profile_path]
+});
+
+static DEFAULT_BUNDLE_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let profile_path = match env::var(BUNDLE_ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => dirs::home_dir().unwrap().join(".bundle").join("config"),
+    };
+    vec![profile_path]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct GemMirror {
+    url: String,
+    /// Bundler 中配置的 rubygems.org 镜像
+    #[serde(default)]
+    bundle_mirror: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl GemMirror {
+    pub fn new(url: String) -> Self {
+        Self {
+            bundle_mirror: url.clone(),
+            url,
+            url_delay: -1,
+        }
+    }
+}
+
+impl Display for GemMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for GemMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 替换 .gemrc 中的 :sources:，url 为 None 时移除以使用官方源
+///
+fn gemrc_config(yaml: &str, url: Option<&str>) -> Result<String> {
+    let mut config = serde_yaml::from_str::<Option<GemrcConfig>>(yaml)?.unwrap_or_default();
+    config.sources = url.map(|url| vec![url.to_string()]).unwrap_or_default();
+    let yaml = serde_yaml::to_string(&config)?;
+    // 没有其他配置时序列化结果为 {}
+    Ok(if yaml.trim() == "{}" {
+        String::new()
+    } else {
+        yaml
+    })
+}
+
+///
+/// 设置 Bundler 中 rubygems.org 的镜像，url 为 None 时移除
+///
+fn bundle_config(yaml: &str, url: Option<&str>) -> Result<String> {
+    let mut config = serde_yaml::from_str::<Option<BundleConfig>>(yaml)?.unwrap_or_default();
+    config.mirror = url.map(String::from);
+    let yaml = serde_yaml::to_string(&config)?;
+    // 没有其他配置时序列化结果为 {}
+    Ok(if yaml.trim() == "{}" {
+        String::new()
+    } else {
+        format!("---\n{}", yaml)
+    })
+}
+
+///
+/// 修改配置文件，内容不变时不写入，文件不存在且新内容为空时不创建
+///
+fn update_config(paths: Vec<PathBuf>, f: impl Fn(&str) -> Result<String>) -> Result<()> {
+    let (exists, yaml) = match read_config(paths.clone()) {
+        Ok((_, yaml)) => (true, yaml),
+        Err(_) => (false, String::new()),
+    };
+    let new_yaml = f(&yaml)?;
+    if new_yaml != yaml && (exists || !new_yaml.is_empty()) {
+        write_config(paths, &new_yaml)?;
+    }
+    Ok(())
+}
+
+///
+/// 同时写入 .gemrc 和 Bundler 配置，url 为 None 时移除镜像以恢复官方源
+///
+fn write_configs(url: Option<&str>) -> Result<()> {
+    update_config(DEFAULT_GEM_PROFILES.to_vec(), |yaml| {
+        gemrc_config(yaml, url)
+    })?;
+    update_config(DEFAULT_BUNDLE_PROFILES.to_vec(), |yaml| {
+        bundle_config(yaml, url)
+    })
+}
+
+impl Reader for GemMirror {
+    fn new_config(&self) -> Result<String> {
+        let yaml = read_config(DEFAULT_GEM_PROFILES.to_vec())
+            .map(|(_, yaml)| yaml)
+            .unwrap_or_default();
+        gemrc_config(&yaml, Some(&self.url))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct GemPackageManager {}
+
+impl MirrorConfigurate for GemPackageManager {
+    type R = GemMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![arg!(-u --url <url>)
+            .help("The url of the mirror")
+            .required(true)]
+    }
+
+    fn name(&self) -> &'static str {
+        "gem"
+    }
+
+    fn current_mirror(&self) -> Option<GemMirror> {
+        let url = read_config(self.get_default_profile_vec())
+            .ok()
+            .and_then(|(_, yaml)| serde_yaml::from_str::<Option<GemrcConfig>>(&yaml).ok()?)
+            .and_then(|config| config.sources.into_iter().next());
+        let bundle_mirror = read_config(DEFAULT_BUNDLE_PROFILES.to_vec())
+            .ok()
+            .and_then(|(_, yaml)| serde_yaml::from_str::<Option<BundleConfig>>(&yaml).ok()?)
+            .and_then(|config| config.mirror);
+        if url.is_none() && bundle_mirror.is_none() {
+            return None;
+        }
+        Some(GemMirror {
+            url: url.unwrap_or_default(),
+            bundle_mirror: bundle_mirror.unwrap_or_default(),
+            url_delay: -1,
+        })
+    }
+
+    fn get_mirrors(&self) -> Vec<GemMirror> {
+        let mirrors = include_str!("../../../mirrors/gem.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let mirror = GemMirror::new(url);
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: GemMirror) {
+        let _ = write_configs(Some(&mirror.url));
+    }
+
+    fn remove_mirror(&self, mirror: GemMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.url == mirror.url || current.bundle_mirror == mirror.url)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        let _ = write_configs(None);
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_GEM_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gemrc_config() {
+        let gemrc =
+            "---\n:backtrace: false\n:sources:\n- https://rubygems.org/\ngem: --no-document\n";
+        assert_eq!(
+            gemrc_config(gemrc, Some("https://gems.ruby-china.com/")).unwrap(),
+            ":sources:\n- https://gems.ruby-china.com/\n:backtrace: false\ngem: --no-document\n"
+        );
+        assert_eq!(
+            gemrc_config(gemrc, None).unwrap(),
+            ":backtrace: false\ngem: --no-document\n"
+        );
+        // 只有镜像配置时移除整个文件
+        let new_gemrc = gemrc_config("", Some("https://gems.ruby-china.com/")).unwrap();
+        assert_eq!(new_gemrc, ":sources:\n- https://gems.ruby-china.com/\n");
+        assert_eq!(gemrc_config(&new_gemrc, None).unwrap(), "");
+    }
+
+    #[test]
+    fn test_bundle_config() {
+        let bundle = "---\nBUNDLE_JOBS: \"4\"\n";
+        let new_bundle = bundle_config(bundle, Some("https://gems.ruby-china.com/")).unwrap();
+        assert_eq!(
+            new_bundle,
+            "---\nBUNDLE_MIRROR__HTTPS://RUBYGEMS__ORG/: https://gems.ruby-china.com/\nBUNDLE_JOBS: '4'\n"
+        );
+        assert_eq!(
+            bundle_config(&new_bundle, None).unwrap(),
+            "---\nBUNDLE_JOBS: '4'\n"
+        );
+        assert_eq!(bundle_config("", None).unwrap(), "");
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+
+///
+/// RubyGems 的 .gemrc，键为 Ruby 符号
+///
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct GemrcConfig {
+    #[serde(rename = ":sources", default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) sources: Vec<String>,
+    /// 其他配置保持原有顺序
+    #[serde(flatten)]
+    extra_fields: Mapping,
+}
+
+///
+/// Bundler 的用户配置 ~/.bundle/config
+///
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct BundleConfig {
+    #[serde(
+        rename = "BUNDLE_MIRROR__HTTPS://RUBYGEMS__ORG/",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(super) mirror: Option<String>,
+    /// 其他配置保持原有顺序
+    #[serde(flatten)]
+    extra_fields: Mapping,
+}
@@ -0,0 +1,239 @@
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+use super::{MirrorConfigurate, Reader};
+
+const ENV_NAME: &str = "GOENV";
+
+/// `go env -w` 使用的配置文件
+static DEFAULT_GO_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let profile_path = match env::var(ENV_NAME) {
+        Ok(value) if !value.is_empty() && value != "off" => PathBuf::from(value),
+        _ => dirs::config_dir().unwrap().join("go").join("env"),
+    };
+    vec![profile_path]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct GoMirror {
+    url: String,
+    #[serde(default)]
+    sumdb: String,
+    #[serde(default)]
+    private: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl GoMirror {
+    pub fn new(url: String, sumdb: String, private: String) -> Self {
+        Self {
+            url,
+            sumdb,
+            private,
+            url_delay: -1,
+        }
+    }
+}
+
+impl Display for GoMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}ms", self.url, self.sumdb, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for GoMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        let sumdb = value["sumdb"].as_str();
+        let private = value["private"].as_str();
+        Self::new(
+            url.unwrap_or_default().to_string(),
+            sumdb.unwrap_or_default().to_string(),
+            private.unwrap_or_default().to_string(),
+        )
+    }
+}
+
+///
+/// 设置 go env 文件中的变量，已存在则替换，不存在则追加，值为空时删除该变量
+///
+fn set_env(properties: &str, vars: &[(&str, &str)]) -> String {
+    let mut new_properties = String::new();
+    let mut written = vec![];
+    for line in properties.lines() {
+        let key = line.split_once('=').map(|(k, _)| k.trim());
+        match vars.iter().find(|(k, _)| Some(*k) == key) {
+            Some((k, v)) => {
+                if !v.is_empty() {
+                    new_properties.push_str(&format!("{}={}\n", k, v));
+                }
+                written.push(*k);
+            }
+            None => {
+                new_properties.push_str(line);
+                new_properties.push('\n');
+            }
+        }
+    }
+    for (k, v) in vars {
+        if !v.is_empty() && !written.contains(k) {
+            new_properties.push_str(&format!("{}={}\n", k, v));
+        }
+    }
+    new_properties
+}
+
+///
+/// 读取 go env 文件中的变量
+///
+fn get_env<'a>(properties: &'a str, key: &str) -> Option<&'a str> {
+    properties.lines().find_map(|line| {
+        let (k, v) = line.split_once('=')?;
+        (k.trim() == key).then_some(v.trim())
+    })
+}
+
+impl Reader for GoMirror {
+    fn new_config(&self) -> Result<String> {
+        let properties = read_config(DEFAULT_GO_PROFILES.to_vec())
+            .map(|(_, properties)| properties)
+            .unwrap_or_default();
+        let proxy = format!("{},direct", self.url);
+        // 未指定 GOSUMDB 和 GOPRIVATE 时保留原有配置
+        let mut vars = vec![("GOPROXY", proxy.as_str())];
+        if !self.sumdb.is_empty() {
+            vars.push(("GOSUMDB", &self.sumdb));
+        }
+        if !self.private.is_empty() {
+            vars.push(("GOPRIVATE", &self.private));
+        }
+        Ok(set_env(&properties, &vars))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct GoPackageManager {}
+
+impl MirrorConfigurate for GoPackageManager {
+    type R = GoMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(-u --url <url>)
+                .help("The url of the module proxy")
+                .required(true),
+            arg!(-s --sumdb <sumdb>)
+                .help("The checksum database, e.g. sum.golang.google.cn")
+                .required(false),
+            arg!(-p --private <private>)
+                .help("Comma-separated module path patterns that bypass the proxy")
+                .required(false),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "go"
+    }
+
+    fn current_mirror(&self) -> Option<GoMirror> {
+        match read_config(self.get_default_profile_vec()) {
+            Ok((_, properties)) => {
+                let proxy = get_env(&properties, "GOPROXY")?;
+                let url = proxy.split([',', '|']).next().unwrap_or_default();
+                let sumdb = get_env(&properties, "GOSUMDB").unwrap_or_default();
+                let private = get_env(&properties, "GOPRIVATE").unwrap_or_default();
+                Some(GoMirror::new(
+                    url.to_string(),
+                    sumdb.to_string(),
+                    private.to_string(),
+                ))
+            }
+            Err(_) => None,
+        }
+    }
+
+    fn get_mirrors(&self) -> Vec<GoMirror> {
+        let mirrors = include_str!("../../../mirrors/go.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let sumdb = args.get_one::<String>("sumdb").cloned().unwrap_or_default();
+        let private = args
+            .get_one::<String>("private")
+            .cloned()
+            .unwrap_or_default();
+        let mirror = GoMirror::new(url, sumdb, private);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: GoMirror) {
+        if let Ok((_, properties)) = read_config(self.get_default_profile_vec()) {
+            let mut new_properties = String::new();
+            for line in properties.lines() {
+                if !line.starts_with("GOPROXY=") || !line.contains(&mirror.url) {
+                    new_properties.push_str(line);
+                    new_properties.push('\n');
+                }
+            }
+            let _ = write_config(self.get_default_profile_vec(), &new_properties);
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, properties)) = read_config(self.get_default_profile_vec()) {
+            let new_properties = set_env(&properties, &[("GOPROXY", ""), ("GOSUMDB", "")]);
+            let _ = write_config(self.get_default_profile_vec(), &new_properties);
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_GO_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_env() {
+        let properties = "GOPROXY=https://proxy.golang.org,direct\nGOPRIVATE=*.corp.example.com\n";
+        let new_properties = set_env(
+            properties,
+            &[
+                ("GOPROXY", "https://goproxy.cn,direct"),
+                ("GOSUMDB", "sum.golang.google.cn"),
+            ],
+        );
+        assert_eq!(
+            new_properties,
+            "GOPROXY=https://goproxy.cn,direct\nGOPRIVATE=*.corp.example.com\nGOSUMDB=sum.golang.google.cn\n"
+        );
+        assert_eq!(
+            set_env(&new_properties, &[("GOPROXY", ""), ("GOSUMDB", "")]),
+            "GOPRIVATE=*.corp.example.com\n"
+        );
+    }
+}
@@ -0,0 +1,461 @@
+mod object;
+
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use object::StackConfig;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{MirrorConfigurate, Reader};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+const CABAL_ENV_NAME: &str = "CABAL_DIR";
+
+const STACK_ENV_NAME: &str = "STACK_ROOT";
+
+const PACKAGE_INDEX: &str = "package-index";
+
+const DOWNLOAD_PREFIX: &str = "download-prefix";
+
+const SETUP_INFO_LOCATIONS: &str = "setup-info-locations";
+
+const SNAPSHOT_LOCATION_BASE: &str = "snapshot-location-base";
+
+/// cabal 默认的 hackage 仓库
+const DEFAULT_REPOSITORY: &str =
+    "repository hackage.haskell.org\n  url: http://hackage.haskell.org/\n";
+
+static DEFAULT_CABAL_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let home = dirs::home_dir().unwrap();
+    let cabal_dir = match env::var(CABAL_ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        // 旧版本使用的目录存在时优先使用
+        Err(_) if home.join(".cabal").join("config").exists() => home.join(".cabal"),
+        Err(_) if cfg!(target_os = "windows") => dirs::config_dir().unwrap().join("cabal"),
+        Err(_) => home.join(".config").join("cabal"),
+    };
+    vec![cabal_dir.join("config")]
+});
+
+static STACK_ROOT: LazyLock<PathBuf> = LazyLock::new(|| match env::var(STACK_ENV_NAME) {
+    Ok(value) => PathBuf::from(value),
+    Err(_) if cfg!(target_os = "windows") => dirs::config_dir().unwrap().join("stack"),
+    Err(_) => dirs::home_dir().unwrap().join(".stack"),
+});
+
+static DEFAULT_STACK_PROFILES: LazyLock<Vec<PathBuf>> =
+    LazyLock::new(|| vec![STACK_ROOT.join("config.yaml")]);
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct HaskellMirror {
+    #[serde(default)]
+    name: String,
+    /// cabal 使用的 hackage 地址
+    hackage: String,
+    /// stack 使用的 stackage 地址
+    stackage: String,
+    /// stack 使用的 hackage 地址
+    #[serde(default)]
+    stack_hackage: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl HaskellMirror {
+    pub(crate) fn new(name: String, hackage: String, stackage: String) -> Self {
+        let hackage = format!("{}/", hackage.trim_end_matches('/'));
+        let stackage = format!("{}/", stackage.trim_end_matches('/'));
+        Self {
+            name,
+            stack_hackage: hackage.clone(),
+            hackage,
+            stackage,
+            url_delay: -1,
+        }
+    }
+
+    fn setup_info_location(&self) -> String {
+        format!("{}stack-setup.yaml", self.stackage)
+    }
+
+    fn snapshot_location_base(&self) -> String {
+        format!("{}stackage-snapshots/", self.stackage)
+    }
+}
+
+impl Display for HaskellMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "{} {}ms", self.hackage, self.url_delay)
+        } else {
+            write!(f, "{}: {} {}ms", self.name, self.hackage, self.url_delay)
+        }
+    }
+}
+
+impl From<serde_json::Value> for HaskellMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let name = value["name"].as_str();
+        let hackage = value["hackage"].as_str();
+        let stackage = value["stackage"].as_str();
+        Self::new(
+            name.unwrap_or_default().to_string(),
+            hackage.unwrap_or_default().to_string(),
+            stackage.unwrap_or_default().to_string(),
+        )
+    }
+}
+
+///
+/// 替换 cabal 配置中的第一个 repository 段，不存在时追加
+///
+fn replace_repository(config: &str, repository: &str) -> String {
+    let mut new_config = String::new();
+    let mut replaced = false;
+    let mut in_stanza = false;
+    for line in config.lines() {
+        // repository 段的内容均有缩进
+        if in_stanza && line.starts_with(char::is_whitespace) && !line.trim().is_empty() {
+            continue;
+        }
+        in_stanza = false;
+        if !replaced && line.starts_with("repository ") {
+            new_config.push_str(repository);
+            replaced = true;
+            in_stanza = true;
+        } else {
+            new_config.push_str(line);
+            new_config.push('\n');
+        }
+    }
+    if !replaced {
+        if !new_config.is_empty() {
+            new_config.push('\n');
+        }
+        new_config.push_str(repository);
+    }
+    new_config
+}
+
+///
+/// 生成指向镜像的 repository 段
+///
+fn mirror_repository(hackage: &str) -> String {
+    let name = Url::parse(hackage)
+        .ok()
+        .and_then(|url| url.host_str().map(String::from))
+        .unwrap_or("mirrors".into());
+    format!("repository {}\n  url: {}\n  secure: True\n", name, hackage)
+}
+
+///
+/// 读取 cabal 配置中第一个 repository 段的地址
+///
+fn repository_url(config: &str) -> Option<&str> {
+    config
+        .lines()
+        .skip_while(|line| !line.starts_with("repository "))
+        .skip(1)
+        .take_while(|line| line.starts_with(char::is_whitespace))
+        .find_map(|line| line.trim().strip_prefix("url:"))
+        .map(str::trim)
+}
+
+///
+/// 替换 stack 配置中的顶层配置项，不存在时追加到末尾，block 为 None 时移除
+///
+/// 按行修改而不使用 serde_yaml 整体写回，以保留 stack 生成的配置中的注释
+///
+fn replace_stack_key(yaml: &str, key: &str, block: Option<&str>) -> String {
+    let mut new_yaml = String::new();
+    let mut replaced = false;
+    let mut in_key = false;
+    for line in yaml.lines() {
+        // 配置项的内容均有缩进，或者是未缩进的列表项
+        if in_key
+            && (line.starts_with("- ")
+                || (line.starts_with(char::is_whitespace) && !line.trim().is_empty()))
+        {
+            continue;
+        }
+        in_key = line
+            .strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace));
+        if !in_key {
+            new_yaml.push_str(line);
+            new_yaml.push('\n');
+        } else if let (Some(block), false) = (block, replaced) {
+            new_yaml.push_str(block);
+            replaced = true;
+        }
+    }
+    if let (Some(block), false) = (block, replaced) {
+        new_yaml.push_str(block);
+    }
+    new_yaml
+}
+
+///
+/// 设置 stack 配置中的 hackage 和 stackage 地址，mirror 为 None 时移除
+///
+fn stack_config(yaml: &str, mirror: Option<&HaskellMirror>) -> String {
+    let blocks = [
+        (
+            PACKAGE_INDEX,
+            mirror.map(|mirror| {
+                format!(
+                    "{}:\n  {}: {}\n",
+                    PACKAGE_INDEX, DOWNLOAD_PREFIX, mirror.hackage
+                )
+            }),
+        ),
+        (
+            SETUP_INFO_LOCATIONS,
+            mirror.map(|mirror| {
+                format!(
+                    "{}:\n- {}\n",
+                    SETUP_INFO_LOCATIONS,
+                    mirror.setup_info_location()
+                )
+            }),
+        ),
+        (
+            SNAPSHOT_LOCATION_BASE,
+            mirror.map(|mirror| {
+                format!(
+                    "{}: {}\n",
+                    SNAPSHOT_LOCATION_BASE,
+                    mirror.snapshot_location_base()
+                )
+            }),
+        ),
+    ];
+    blocks.iter().fold(yaml.to_string(), |yaml, (key, block)| {
+        replace_stack_key(&yaml, key, block.as_deref())
+    })
+}
+
+///
+/// 写入 stack 配置，未安装 stack 时跳过
+///
+fn write_stack_config(mirror: Option<&HaskellMirror>) -> Result<()> {
+    if !STACK_ROOT.exists() {
+        return Ok(());
+    }
+    let yaml = read_config(DEFAULT_STACK_PROFILES.to_vec())
+        .map(|(_, yaml)| yaml)
+        .unwrap_or_default();
+    write_config(
+        DEFAULT_STACK_PROFILES.to_vec(),
+        &stack_config(&yaml, mirror),
+    )
+}
+
+impl Reader for HaskellMirror {
+    fn new_config(&self) -> Result<String> {
+        let config = read_config(DEFAULT_CABAL_PROFILES.to_vec())
+            .map(|(_, config)| config)
+            .unwrap_or_default();
+        Ok(replace_repository(
+            &config,
+            &mirror_repository(&self.hackage),
+        ))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct HaskellPackageManager {}
+
+impl MirrorConfigurate for HaskellPackageManager {
+    type R = HaskellMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(--hackage <HACKAGE>)
+                .help("The url of the hackage mirror, e.g. https://mirrors.tuna.tsinghua.edu.cn/hackage/")
+                .required(true),
+            arg!(--stackage <STACKAGE>)
+                .help("The url of the stackage mirror, e.g. https://mirrors.tuna.tsinghua.edu.cn/stackage/")
+                .required(true),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "haskell"
+    }
+
+    fn current_mirror(&self) -> Option<HaskellMirror> {
+        let hackage = read_config(self.get_default_profile_vec())
+            .ok()
+            .and_then(|(_, config)| repository_url(&config).map(String::from));
+        let stack = read_config(DEFAULT_STACK_PROFILES.to_vec())
+            .ok()
+            .and_then(|(_, yaml)| serde_yaml::from_str::<Option<StackConfig>>(&yaml).ok()?);
+        let stack_hackage = stack
+            .as_ref()
+            .and_then(|config| config.package_index.as_ref()?[DOWNLOAD_PREFIX].as_str())
+            .map(String::from);
+        let stackage = stack
+            .as_ref()
+            .and_then(|config| config.setup_info_locations.first())
+            .and_then(|location| location.strip_suffix("stack-setup.yaml"))
+            .map(String::from);
+        if hackage.is_none() && stack_hackage.is_none() {
+            return None;
+        }
+        Some(HaskellMirror {
+            name: String::new(),
+            hackage: hackage.unwrap_or_default(),
+            stackage: stackage.unwrap_or_default(),
+            stack_hackage: stack_hackage.unwrap_or_default(),
+            url_delay: -1,
+        })
+    }
+
+    fn get_mirrors(&self) -> Vec<HaskellMirror> {
+        let mirrors = include_str!("../../../mirrors/haskell.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.hackage.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let hackage = args
+            .get_one::<String>("hackage")
+            .cloned()
+            .unwrap_or_default();
+        let stackage = args
+            .get_one::<String>("stackage")
+            .cloned()
+            .unwrap_or_default();
+        let mirror = HaskellMirror::new(String::new(), hackage, stackage);
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: HaskellMirror) {
+        if let Ok(new_config) = mirror.new_config() {
+            let _ = write_config(self.get_default_profile_vec(), &new_config);
+        }
+        let _ = write_stack_config(Some(&mirror));
+    }
+
+    fn remove_mirror(&self, mirror: HaskellMirror) {
+        if self.current_mirror().is_some_and(|current| {
+            current.hackage == mirror.hackage || current.stack_hackage == mirror.hackage
+        }) {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, config)) = read_config(self.get_default_profile_vec()) {
+            let new_config = replace_repository(&config, DEFAULT_REPOSITORY);
+            let _ = write_config(self.get_default_profile_vec(), &new_config);
+        }
+        let _ = write_stack_config(None);
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_CABAL_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_repository() {
+        let config = r#"repository hackage.haskell.org
+  url: http://hackage.haskell.org/
+  -- secure: True
+  -- root-keys:
+
+-- ignore-expiry: False
+jobs: $ncpus
+"#;
+        let new_config = replace_repository(
+            config,
+            &mirror_repository("https://mirrors.tuna.tsinghua.edu.cn/hackage/"),
+        );
+        assert_eq!(
+            new_config,
+            r#"repository mirrors.tuna.tsinghua.edu.cn
+  url: https://mirrors.tuna.tsinghua.edu.cn/hackage/
+  secure: True
+
+-- ignore-expiry: False
+jobs: $ncpus
+"#
+        );
+        assert_eq!(
+            repository_url(&new_config),
+            Some("https://mirrors.tuna.tsinghua.edu.cn/hackage/")
+        );
+    }
+
+    #[test]
+    fn test_stack_config() {
+        let mirror = HaskellMirror::new(
+            "tuna".into(),
+            "https://mirrors.tuna.tsinghua.edu.cn/hackage".into(),
+            "https://mirrors.tuna.tsinghua.edu.cn/stackage".into(),
+        );
+        let config = r#"# This file contains default non-project-specific settings for Stack.
+templates:
+  params:
+    # author-name:
+    # github-username:
+setup-info-locations:
+- https://example.com/stack-setup.yaml
+allow-newer: true
+"#;
+        let yaml = stack_config(config, Some(&mirror));
+        assert_eq!(
+            yaml,
+            r#"# This file contains default non-project-specific settings for Stack.
+templates:
+  params:
+    # author-name:
+    # github-username:
+setup-info-locations:
+- https://mirrors.tuna.tsinghua.edu.cn/stackage/stack-setup.yaml
+allow-newer: true
+package-index:
+  download-prefix: https://mirrors.tuna.tsinghua.edu.cn/hackage/
+snapshot-location-base: https://mirrors.tuna.tsinghua.edu.cn/stackage/stackage-snapshots/
+"#
+        );
+        let config = serde_yaml::from_str::<StackConfig>(&yaml).unwrap();
+        assert_eq!(
+            config.package_index.unwrap()[DOWNLOAD_PREFIX].as_str(),
+            Some("https://mirrors.tuna.tsinghua.edu.cn/hackage/")
+        );
+        assert_eq!(
+            stack_config(&yaml, None),
+            r#"# This file contains default non-project-specific settings for Stack.
+templates:
+  params:
+    # author-name:
+    # github-username:
+allow-newer: true
+"#
+        );
+    }
+}
@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use serde_yaml::Value;
+
+///
+/// stack 全局配置 config.yaml 中读取的配置项，写入时按行修改
+///
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct StackConfig {
+    #[serde(rename = "package-index")]
+    pub(super) package_index: Option<Value>,
+    #[serde(rename = "setup-info-locations", default)]
+    pub(super) setup_info_locations: Vec<String>,
+}
@@ -0,0 +1,342 @@
+mod object;
+
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use object::{repositories, repositories_mut, repository, set_repository_url};
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+use std::{collections::BTreeMap, env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+use super::{MirrorConfigurate, Reader};
+
+const ENV_NAME: &str = "HELM_REPOSITORY_CONFIG";
+
+const CONFIG_HOME_ENV_NAME: &str = "HELM_CONFIG_HOME";
+
+/// 支持替换的上游仓库，第一个地址为 reset 时恢复的地址
+const UPSTREAMS: [(&str, &[&str]); 3] = [
+    ("bitnami", &["https://charts.bitnami.com/bitnami"]),
+    (
+        "stable",
+        &[
+            "https://charts.helm.sh/stable",
+            "https://kubernetes-charts.storage.googleapis.com",
+        ],
+    ),
+    (
+        "ingress-nginx",
+        &["https://kubernetes.github.io/ingress-nginx"],
+    ),
+];
+
+static DEFAULT_HELM_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let path = match env::var(ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => {
+            let config_home = match env::var(CONFIG_HOME_ENV_NAME) {
+                Ok(value) => PathBuf::from(value),
+                Err(_) if cfg!(target_os = "macos") => dirs::home_dir()
+                    .unwrap()
+                    .join("Library")
+                    .join("Preferences")
+                    .join("helm"),
+                Err(_) => dirs::config_dir().unwrap().join("helm"),
+            };
+            config_home.join("repositories.yaml")
+        }
+    };
+    vec![path]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct HelmMirror {
+    url: String,
+    /// 上游仓库（bitnami、stable、ingress-nginx）对应的镜像地址
+    #[serde(default)]
+    repositories: BTreeMap<String, String>,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl HelmMirror {
+    pub fn new(url: String, repositories: BTreeMap<String, String>) -> Self {
+        Self {
+            url,
+            repositories,
+            url_delay: -1,
+        }
+    }
+}
+
+impl Display for HelmMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for HelmMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        let repositories = serde_json::from_value(value["repositories"].clone());
+        Self::new(
+            url.unwrap_or_default().to_string(),
+            repositories.unwrap_or_default(),
+        )
+    }
+}
+
+fn catalog() -> Vec<HelmMirror> {
+    serde_json::from_str(include_str!("../../../mirrors/helm.json")).unwrap_or_default()
+}
+
+fn same_url(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+///
+/// 判断仓库对应的上游，只按上游地址与内置镜像地址匹配，不按仓库名称匹配以免覆盖私有仓库
+///
+fn upstream_of(url: &str, catalog: &[HelmMirror]) -> Option<&'static str> {
+    UPSTREAMS
+        .iter()
+        .find(|(_, urls)| urls.iter().any(|u| same_url(u, url)))
+        .or_else(|| {
+            UPSTREAMS.iter().find(|(key, _)| {
+                catalog.iter().any(|mirror| {
+                    mirror
+                        .repositories
+                        .get(*key)
+                        .is_some_and(|u| same_url(u, url))
+                })
+            })
+        })
+        .map(|(key, _)| *key)
+}
+
+fn is_upstream(url: &str) -> bool {
+    UPSTREAMS
+        .iter()
+        .any(|(_, urls)| urls.iter().any(|u| same_url(u, url)))
+}
+
+fn upstream_url(key: &str) -> Option<&'static str> {
+    UPSTREAMS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, urls)| urls[0])
+}
+
+///
+/// 替换已添加的上游仓库地址，f 接收上游名称与当前地址，返回新地址
+///
+fn rewrite_urls(
+    yaml: &str,
+    catalog: &[HelmMirror],
+    f: impl Fn(&str, &str) -> Option<String>,
+) -> Result<String> {
+    let mut config = serde_yaml::from_str::<Mapping>(yaml)?;
+    for repo in repositories_mut(&mut config) {
+        let (_, url) = repository(repo);
+        let new_url = upstream_of(url, catalog).and_then(|key| f(key, url));
+        if let Some(new_url) = new_url {
+            set_repository_url(repo, &new_url);
+        }
+    }
+    Ok(serde_yaml::to_string(&config)?)
+}
+
+impl Reader for HelmMirror {
+    fn new_config(&self) -> Result<String> {
+        // 只替换已通过 helm repo add 添加的仓库
+        let (_, yaml) = read_config(DEFAULT_HELM_PROFILES.to_vec())?;
+        rewrite_urls(&yaml, &catalog(), |key, _| {
+            self.repositories.get(key).cloned()
+        })
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct HelmPackageManager {}
+
+impl HelmPackageManager {
+    fn rewrite_profile(&self, f: impl Fn(&str, &str) -> Option<String>) {
+        if let Ok((_, yaml)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(new_config) = rewrite_urls(&yaml, &catalog(), f) {
+                let _ = write_config(self.get_default_profile_vec(), &new_config);
+            }
+        }
+    }
+}
+
+impl MirrorConfigurate for HelmPackageManager {
+    type R = HelmMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(--bitnami <url>)
+                .help("The mirror url of the bitnami repository")
+                .required_unless_present_any(["stable", "ingress-nginx"]),
+            arg!(--stable <url>)
+                .help("The mirror url of the stable repository archive")
+                .required_unless_present_any(["bitnami", "ingress-nginx"]),
+            arg!(--"ingress-nginx" <url>)
+                .help("The mirror url of the ingress-nginx repository")
+                .required_unless_present_any(["bitnami", "stable"]),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "helm"
+    }
+
+    fn current_mirror(&self) -> Option<HelmMirror> {
+        let (_, yaml) = read_config(self.get_default_profile_vec()).ok()?;
+        let config = serde_yaml::from_str::<Mapping>(&yaml).ok()?;
+        let catalog = catalog();
+        let repositories: BTreeMap<String, String> = repositories(&config)
+            .into_iter()
+            .map(repository)
+            .filter(|(_, url)| !is_upstream(url))
+            .filter_map(|(_, url)| {
+                upstream_of(url, &catalog).map(|key| (key.to_string(), url.to_string()))
+            })
+            .collect();
+        let url = repositories.values().next()?.clone();
+        // 优先显示内置镜像的名称
+        let mirror = catalog.into_iter().find(|mirror| {
+            mirror
+                .repositories
+                .values()
+                .any(|u| repositories.values().any(|url| same_url(u, url)))
+        });
+        Some(mirror.unwrap_or(HelmMirror::new(url, repositories)))
+    }
+
+    fn get_mirrors(&self) -> Vec<HelmMirror> {
+        let mirrors = include_str!("../../../mirrors/helm.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let repositories: BTreeMap<String, String> = UPSTREAMS
+            .iter()
+            .filter_map(|(key, _)| {
+                let url = args.get_one::<String>(key)?;
+                Some((key.to_string(), url.clone()))
+            })
+            .collect();
+        let url = repositories.values().next().cloned().unwrap_or_default();
+        let mirror = HelmMirror::new(url, repositories);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: HelmMirror) {
+        self.rewrite_profile(|key, url| {
+            let used = mirror
+                .repositories
+                .get(key)
+                .is_some_and(|u| same_url(u, url));
+            used.then(|| upstream_url(key).map(String::from)).flatten()
+        });
+    }
+
+    fn reset_mirrors(&self) {
+        self.rewrite_profile(|key, url| {
+            if is_upstream(url) {
+                return None;
+            }
+            upstream_url(key).map(String::from)
+        });
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_HELM_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_urls() {
+        let yaml = r#"apiVersion: ''
+generated: 0001-01-01T00:00:00Z
+repositories:
+- name: bitnami
+  url: https://charts.bitnami.com/bitnami
+  username: ''
+- name: my-stable
+  url: https://charts.helm.sh/stable/
+- name: jetstack
+  url: https://charts.jetstack.io
+- name: bitnami
+  url: https://charts.corp.example/bitnami
+"#;
+        let catalog = catalog();
+        let mirror = catalog
+            .iter()
+            .find(|mirror| mirror.url == "https://mirror.azure.cn/kubernetes/charts")
+            .unwrap();
+        let new_yaml = rewrite_urls(yaml, &catalog, |key, _| {
+            mirror.repositories.get(key).cloned()
+        })
+        .unwrap();
+        assert_eq!(
+            new_yaml,
+            r#"apiVersion: ''
+generated: 0001-01-01T00:00:00Z
+repositories:
+- name: bitnami
+  url: https://charts.bitnami.com/bitnami
+  username: ''
+- name: my-stable
+  url: https://mirror.azure.cn/kubernetes/charts
+- name: jetstack
+  url: https://charts.jetstack.io
+- name: bitnami
+  url: https://charts.corp.example/bitnami
+"#
+        );
+
+        let reset_yaml = rewrite_urls(&new_yaml, &catalog, |key, url| {
+            (!is_upstream(url))
+                .then(|| upstream_url(key).map(String::from))
+                .flatten()
+        })
+        .unwrap();
+        assert!(reset_yaml.contains("url: https://charts.bitnami.com/bitnami"));
+        assert!(reset_yaml.contains("url: https://charts.helm.sh/stable\n"));
+        assert!(reset_yaml.contains("url: https://charts.jetstack.io"));
+        assert!(reset_yaml.contains("url: https://charts.corp.example/bitnami"));
+
+        // 同名的私有仓库不会被替换
+        let bitnami = catalog
+            .iter()
+            .find(|mirror| mirror.repositories.contains_key("bitnami"))
+            .unwrap();
+        let new_yaml = rewrite_urls(yaml, &catalog, |key, _| {
+            bitnami.repositories.get(key).cloned()
+        })
+        .unwrap();
+        assert!(new_yaml.contains("url: https://charts.corp.example/bitnami"));
+        assert!(!new_yaml.contains("url: https://charts.bitnami.com/bitnami"));
+    }
+}
@@ -0,0 +1,33 @@
+use serde_yaml::{Mapping, Value};
+
+///
+/// helm 仓库配置 repositories.yaml 中的仓库列表，仓库的其他字段（认证、证书等）保持原有顺序
+///
+pub(super) fn repositories(config: &Mapping) -> Vec<&Mapping> {
+    config
+        .get("repositories")
+        .and_then(Value::as_sequence)
+        .map(|repos| repos.iter().filter_map(Value::as_mapping).collect())
+        .unwrap_or_default()
+}
+
+pub(super) fn repositories_mut(config: &mut Mapping) -> Vec<&mut Mapping> {
+    config
+        .get_mut("repositories")
+        .and_then(Value::as_sequence_mut)
+        .map(|repos| repos.iter_mut().filter_map(Value::as_mapping_mut).collect())
+        .unwrap_or_default()
+}
+
+///
+/// 仓库的名称与地址
+///
+pub(super) fn repository(repository: &Mapping) -> (&str, &str) {
+    let name = repository.get("name").and_then(Value::as_str);
+    let url = repository.get("url").and_then(Value::as_str);
+    (name.unwrap_or_default(), url.unwrap_or_default())
+}
+
+pub(super) fn set_repository_url(repository: &mut Mapping, url: &str) {
+    repository.insert("url".into(), url.into());
+}
@@ -0,0 +1,182 @@
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+use super::{MirrorConfigurate, Reader};
+
+const ENV_NAME: &str = "HEX_HOME";
+
+/// 优先级高于 hex.config 的环境变量
+const MIRROR_ENV_NAME: &str = "HEX_MIRROR";
+
+const MIRROR_KEY: &str = "mirror_url";
+
+static DEFAULT_HEX_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let profile_path = match env::var(ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => dirs::home_dir().unwrap().join(".hex"),
+    };
+    vec![profile_path.join("hex.config")]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct HexMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl HexMirror {
+    pub fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for HexMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for HexMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 设置 hex.config 中的配置项，如 {mirror_url,<<"https://hexpm.upyun.com">>}.，值为 None 时删除
+///
+fn set_term(config: &str, key: &str, value: Option<&str>) -> String {
+    let prefix = format!("{{{},", key);
+    let mut new_config = String::new();
+    for line in config.lines() {
+        if !line.trim_start().starts_with(&prefix) {
+            new_config.push_str(line);
+            new_config.push('\n');
+        }
+    }
+    if let Some(value) = value {
+        new_config.push_str(&format!("{}<<\"{}\">>}}.\n", prefix, value));
+    }
+    new_config
+}
+
+///
+/// 读取 hex.config 中的配置项
+///
+fn get_term<'a>(config: &'a str, key: &str) -> Option<&'a str> {
+    let prefix = format!("{{{},", key);
+    config.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix(&prefix)?
+            .trim_start()
+            .strip_prefix("<<\"")?
+            .strip_suffix("\">>}.")
+    })
+}
+
+impl Reader for HexMirror {
+    fn new_config(&self) -> Result<String> {
+        let config = read_config(DEFAULT_HEX_PROFILES.to_vec())
+            .map(|(_, config)| config)
+            .unwrap_or_default();
+        Ok(set_term(&config, MIRROR_KEY, Some(&self.url)))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct HexPackageManager {}
+
+impl MirrorConfigurate for HexPackageManager {
+    type R = HexMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![arg!(-u --url <url>)
+            .help("The url of the hex mirror")
+            .required(true)]
+    }
+
+    fn name(&self) -> &'static str {
+        "hex"
+    }
+
+    fn current_mirror(&self) -> Option<HexMirror> {
+        // HEX_MIRROR 会覆盖 hex.config 中的配置
+        if let Ok(url) = env::var(MIRROR_ENV_NAME) {
+            return Some(HexMirror::new(url));
+        }
+        let (_, config) = read_config(self.get_default_profile_vec()).ok()?;
+        get_term(&config, MIRROR_KEY).map(|url| HexMirror::new(url.to_string()))
+    }
+
+    fn get_mirrors(&self) -> Vec<HexMirror> {
+        let mirrors = include_str!("../../../mirrors/hex.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let mirror = HexMirror::new(url);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: HexMirror) {
+        if let Ok((_, config)) = read_config(self.get_default_profile_vec()) {
+            if get_term(&config, MIRROR_KEY) == Some(&mirror.url) {
+                let new_config = set_term(&config, MIRROR_KEY, None);
+                let _ = write_config(self.get_default_profile_vec(), &new_config);
+            }
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, config)) = read_config(self.get_default_profile_vec()) {
+            let new_config = set_term(&config, MIRROR_KEY, None);
+            let _ = write_config(self.get_default_profile_vec(), &new_config);
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_HEX_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_term() {
+        let config = "{api_key,<<\"secret\">>}.\n{mirror_url,<<\"https://repo.hex.pm\">>}.\n";
+        assert_eq!(get_term(config, MIRROR_KEY), Some("https://repo.hex.pm"));
+        let new_config = set_term(config, MIRROR_KEY, Some("https://hexpm.upyun.com"));
+        assert_eq!(
+            new_config,
+            "{api_key,<<\"secret\">>}.\n{mirror_url,<<\"https://hexpm.upyun.com\">>}.\n"
+        );
+        assert_eq!(
+            set_term(&new_config, MIRROR_KEY, None),
+            "{api_key,<<\"secret\">>}.\n"
+        );
+    }
+}
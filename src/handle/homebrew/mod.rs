@@ -0,0 +1,228 @@
+use crate::utils::{
+    net_utils::test_connection,
+    profile_utils::{read_env_block, remove_env_block, shell_profiles, write_env_block, Shell},
+};
+use anyhow::Result;
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf};
+
+use super::{MirrorConfigurate, Reader};
+
+/// shell 启动文件中配置块的名称
+const BLOCK_NAME: &str = "homebrew";
+
+const BREW_GIT_REMOTE: &str = "HOMEBREW_BREW_GIT_REMOTE";
+
+const CORE_GIT_REMOTE: &str = "HOMEBREW_CORE_GIT_REMOTE";
+
+const BOTTLE_DOMAIN: &str = "HOMEBREW_BOTTLE_DOMAIN";
+
+const API_DOMAIN: &str = "HOMEBREW_API_DOMAIN";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct HomebrewMirror {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    brew_git_remote: String,
+    #[serde(default)]
+    core_git_remote: String,
+    bottle_domain: String,
+    #[serde(default)]
+    api_domain: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl HomebrewMirror {
+    pub(crate) fn new(
+        name: String,
+        brew_git_remote: String,
+        core_git_remote: String,
+        bottle_domain: String,
+        api_domain: String,
+    ) -> Self {
+        Self {
+            name,
+            brew_git_remote,
+            core_git_remote,
+            bottle_domain,
+            api_domain,
+            url_delay: -1,
+        }
+    }
+
+    ///
+    /// 需要写入的环境变量，未指定的变量不写入
+    ///
+    fn vars(&self) -> Vec<(&str, &str)> {
+        [
+            (BREW_GIT_REMOTE, self.brew_git_remote.as_str()),
+            (CORE_GIT_REMOTE, self.core_git_remote.as_str()),
+            (BOTTLE_DOMAIN, self.bottle_domain.as_str()),
+            (API_DOMAIN, self.api_domain.as_str()),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+    }
+}
+
+impl Display for HomebrewMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "{} {}ms", self.bottle_domain, self.url_delay)
+        } else {
+            write!(
+                f,
+                "{}: {} {}ms",
+                self.name, self.bottle_domain, self.url_delay
+            )
+        }
+    }
+}
+
+impl From<serde_json::Value> for HomebrewMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let get = |key: &str| value[key].as_str().unwrap_or_default().to_string();
+        Self::new(
+            get("name"),
+            get("brew_git_remote"),
+            get("core_git_remote"),
+            get("bottle_domain"),
+            get("api_domain"),
+        )
+    }
+}
+
+impl Reader for HomebrewMirror {
+    ///
+    /// 写入 bash、zsh 启动文件的配置块内容
+    ///
+    fn new_config(&self) -> Result<String> {
+        Ok(Shell::Posix.render_env(&self.vars()))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct HomebrewPackageManager {}
+
+impl MirrorConfigurate for HomebrewPackageManager {
+    type R = HomebrewMirror;
+
+    fn support(&self) -> bool {
+        !cfg!(target_os = "windows")
+    }
+
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(-b --bottle <BOTTLE_DOMAIN>)
+                .help("The url of HOMEBREW_BOTTLE_DOMAIN")
+                .required(true),
+            arg!(-a --api <API_DOMAIN>)
+                .help("The url of HOMEBREW_API_DOMAIN")
+                .required(false),
+            arg!(--brew <BREW_GIT_REMOTE>)
+                .help("The url of HOMEBREW_BREW_GIT_REMOTE")
+                .required(false),
+            arg!(--core <CORE_GIT_REMOTE>)
+                .help("The url of HOMEBREW_CORE_GIT_REMOTE")
+                .required(false),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "homebrew"
+    }
+
+    fn current_mirror(&self) -> Option<HomebrewMirror> {
+        let vars = read_env_block(BLOCK_NAME);
+        let get = |key: &str| {
+            vars.iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                // 配置块不存在时读取当前环境变量
+                .or_else(|| env::var(key).ok())
+        };
+        Some(HomebrewMirror::new(
+            String::new(),
+            get(BREW_GIT_REMOTE).unwrap_or_default(),
+            get(CORE_GIT_REMOTE).unwrap_or_default(),
+            get(BOTTLE_DOMAIN)?,
+            get(API_DOMAIN).unwrap_or_default(),
+        ))
+    }
+
+    fn get_mirrors(&self) -> Vec<HomebrewMirror> {
+        let mirrors = include_str!("../../../mirrors/homebrew.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.bottle_domain.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let get = |key: &str| args.get_one::<String>(key).cloned().unwrap_or_default();
+        let mirror = HomebrewMirror::new(
+            String::new(),
+            get("brew"),
+            get("core"),
+            get("bottle"),
+            get("api"),
+        );
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: HomebrewMirror) {
+        let _ = write_env_block(BLOCK_NAME, &mirror.vars());
+    }
+
+    fn remove_mirror(&self, mirror: HomebrewMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.bottle_domain == mirror.bottle_domain)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        let _ = remove_env_block(BLOCK_NAME);
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        shell_profiles().into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen() {
+        let mirror = HomebrewMirror::new(
+            "ustc".into(),
+            String::new(),
+            String::new(),
+            "https://mirrors.ustc.edu.cn/homebrew-bottles".into(),
+            "https://mirrors.ustc.edu.cn/homebrew-bottles/api".into(),
+        );
+        assert_eq!(
+            mirror.new_config().unwrap(),
+            "export HOMEBREW_BOTTLE_DOMAIN=\"https://mirrors.ustc.edu.cn/homebrew-bottles\"\nexport HOMEBREW_API_DOMAIN=\"https://mirrors.ustc.edu.cn/homebrew-bottles/api\""
+        );
+    }
+}
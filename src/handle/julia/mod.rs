@@ -0,0 +1,191 @@
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+    profile_utils::{get_block, read_env_block, remove_env_block, replace_block, write_env_block},
+};
+use anyhow::Result;
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+use super::{MirrorConfigurate, Reader};
+
+const ENV_NAME: &str = "JULIA_DEPOT_PATH";
+
+const PKG_SERVER: &str = "JULIA_PKG_SERVER";
+
+/// shell 启动文件与 startup.jl 中配置块的名称
+const BLOCK_NAME: &str = "julia";
+
+static DEFAULT_JULIA_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    // JULIA_DEPOT_PATH 中的第一个目录为用户目录
+    let depot = env::var(ENV_NAME)
+        .ok()
+        .and_then(|value| env::split_paths(&value).find(|path| !path.as_os_str().is_empty()))
+        .unwrap_or(dirs::home_dir().unwrap().join(".julia"));
+    vec![depot.join("config").join("startup.jl")]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct JuliaMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl JuliaMirror {
+    pub fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for JuliaMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for JuliaMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 从 startup.jl 配置块中解析 JULIA_PKG_SERVER
+///
+fn parse_startup(block: &str) -> Option<&str> {
+    block.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() != format!("ENV[\"{}\"]", PKG_SERVER) {
+            return None;
+        }
+        Some(value.trim().trim_matches('"'))
+    })
+}
+
+impl Reader for JuliaMirror {
+    fn new_config(&self) -> Result<String> {
+        let startup = read_config(DEFAULT_JULIA_PROFILES.to_vec())
+            .map(|(_, startup)| startup)
+            .unwrap_or_default();
+        let block = format!("ENV[\"{}\"] = \"{}\"", PKG_SERVER, self.url);
+        replace_block(&startup, BLOCK_NAME, Some(&block))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct JuliaPackageManager {}
+
+impl MirrorConfigurate for JuliaPackageManager {
+    type R = JuliaMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![arg!(-u --url <url>)
+            .help("The url of the package server")
+            .required(true)]
+    }
+
+    fn name(&self) -> &'static str {
+        "julia"
+    }
+
+    fn current_mirror(&self) -> Option<JuliaMirror> {
+        let startup = read_config(self.get_default_profile_vec())
+            .ok()
+            .and_then(|(_, startup)| {
+                let block = get_block(&startup, BLOCK_NAME)?;
+                parse_startup(&block).map(String::from)
+            });
+        startup
+            .or_else(|| {
+                read_env_block(BLOCK_NAME)
+                    .into_iter()
+                    .find_map(|(k, v)| (k == PKG_SERVER).then_some(v))
+            })
+            .or_else(|| env::var(PKG_SERVER).ok())
+            .map(JuliaMirror::new)
+    }
+
+    fn get_mirrors(&self) -> Vec<JuliaMirror> {
+        let mirrors = include_str!("../../../mirrors/julia.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let mirror = JuliaMirror::new(url);
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: JuliaMirror) {
+        if let Ok(new_config) = mirror.new_config() {
+            let _ = write_config(self.get_default_profile_vec(), &new_config);
+        }
+        // Windows 下没有 shell 启动文件，只写入 startup.jl
+        if !cfg!(target_os = "windows") {
+            let _ = write_env_block(BLOCK_NAME, &[(PKG_SERVER, &mirror.url)]);
+        }
+    }
+
+    fn remove_mirror(&self, mirror: JuliaMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.url == mirror.url)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, startup)) = read_config(self.get_default_profile_vec()) {
+            if let Ok(new_startup) = replace_block(&startup, BLOCK_NAME, None) {
+                if new_startup != startup {
+                    let _ = write_config(self.get_default_profile_vec(), &new_startup);
+                }
+            }
+        }
+        if !cfg!(target_os = "windows") {
+            let _ = remove_env_block(BLOCK_NAME);
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_JULIA_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_startup() {
+        let mirror = JuliaMirror::new("https://mirrors.tuna.tsinghua.edu.cn/julia".into());
+        let startup = "using Revise\n";
+        let block = format!("ENV[\"{}\"] = \"{}\"", PKG_SERVER, mirror.url);
+        let new_startup = replace_block(startup, BLOCK_NAME, Some(&block)).unwrap();
+        assert_eq!(
+            new_startup,
+            "using Revise\n\n# >>> mirrors julia >>>\nENV[\"JULIA_PKG_SERVER\"] = \"https://mirrors.tuna.tsinghua.edu.cn/julia\"\n# <<< mirrors julia <<<\n"
+        );
+        assert_eq!(
+            parse_startup(&get_block(&new_startup, BLOCK_NAME).unwrap()),
+            Some("https://mirrors.tuna.tsinghua.edu.cn/julia")
+        );
+    }
+}
@@ -0,0 +1,385 @@
+mod object;
+
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use crate::utils::net_utils::test_connection;
+use anyhow::Result;
+use clap::arg;
+use object::RegistriesConfig;
+use process_arg_derive::ProcessArg;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::file_utils::read_config;
+
+use super::{write_config, MirrorConfigurate, Reader};
+
+pub(crate) use os_specific::*;
+
+/// 配置镜像的上游仓库
+const DOCKER_HUB: &str = "docker.io";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct K3sMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl K3sMirror {
+    pub(crate) fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for K3sMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for K3sMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 修改 registries.yaml，没有剩余配置时返回空字符串以删除文件
+///
+fn update_registries(yaml: &str, f: impl Fn(&mut RegistriesConfig)) -> Result<String> {
+    let mut config = if yaml.trim().is_empty() {
+        RegistriesConfig::default()
+    } else {
+        serde_yaml::from_str::<RegistriesConfig>(yaml)?
+    };
+    f(&mut config);
+    if config.mirrors.is_empty() && config.extra_fields.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(serde_yaml::to_string(&config)?)
+}
+
+///
+/// 将镜像放在 endpoint 列表的最前面
+///
+fn prepend_endpoint(config: &mut RegistriesConfig, url: &str) {
+    let mut endpoints = config.endpoints(DOCKER_HUB);
+    endpoints.retain(|endpoint| endpoint != url);
+    endpoints.insert(0, url.to_string());
+    config.set_endpoints(DOCKER_HUB, endpoints);
+}
+
+///
+/// 移除指定的 endpoint，保留用户手动配置的其他 endpoint
+///
+fn remove_endpoints(config: &mut RegistriesConfig, urls: &[&str]) {
+    let mut endpoints = config.endpoints(DOCKER_HUB);
+    endpoints.retain(|endpoint| {
+        !urls
+            .iter()
+            .any(|url| url.trim_end_matches('/') == endpoint.trim_end_matches('/'))
+    });
+    config.set_endpoints(DOCKER_HUB, endpoints);
+}
+
+#[cfg(target_os = "linux")]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    static DEFAULT_K3S_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+        vec![
+            PathBuf::from("/etc/rancher/k3s/registries.yaml"),
+            PathBuf::from("/etc/rancher/rke2/registries.yaml"),
+        ]
+    });
+
+    ///
+    /// 已安装的 k3s、rke2 的配置文件，都未安装时使用 k3s 的配置文件
+    ///
+    fn target_profiles() -> Vec<PathBuf> {
+        let profiles: Vec<PathBuf> = DEFAULT_K3S_PROFILES
+            .iter()
+            .filter(|path| path.parent().is_some_and(|dir| dir.exists()))
+            .cloned()
+            .collect();
+        if profiles.is_empty() {
+            DEFAULT_K3S_PROFILES[..1].to_vec()
+        } else {
+            profiles
+        }
+    }
+
+    impl Reader for K3sMirror {
+        ///
+        /// 只包含该镜像的 registries.yaml，写入时由 set_mirror 分别合并到每个目标配置文件
+        ///
+        fn new_config(&self) -> Result<String> {
+            update_registries("", |config| prepend_endpoint(config, &self.url))
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct K3sPackageManager {}
+
+    impl K3sPackageManager {
+        ///
+        /// 修改所有目标配置文件
+        ///
+        fn update_profiles(&self, f: impl Fn(&mut RegistriesConfig)) {
+            for path in target_profiles() {
+                let yaml = read_config(vec![path.clone()])
+                    .map(|(_, yaml)| yaml)
+                    .unwrap_or_default();
+                if let Ok(new_config) = update_registries(&yaml, &f) {
+                    if new_config != yaml {
+                        let _ = write_config(vec![path], &new_config);
+                    }
+                }
+            }
+        }
+
+        ///
+        /// 按顺序写入多个 endpoint，排在前面的优先使用
+        ///
+        pub(crate) fn set_mirrors(&self, mirrors: Vec<K3sMirror>) {
+            let urls: Vec<String> = mirrors.into_iter().map(|m| m.url).collect();
+            self.update_profiles(|config| config.set_endpoints(DOCKER_HUB, urls.clone()));
+        }
+
+        ///
+        /// 按延迟从低到高写入内置镜像中最快的 count 个
+        ///
+        pub(crate) fn rank_mirrors(&self, count: usize) {
+            let mut mirrors: Vec<K3sMirror> = self
+                .get_mirrors()
+                .into_iter()
+                .filter(|m| m.url_delay >= 0)
+                .collect();
+            mirrors.sort_by_key(|m| m.url_delay);
+            mirrors.truncate(count);
+            if !mirrors.is_empty() {
+                self.set_mirrors(mirrors);
+            }
+        }
+    }
+
+    impl MirrorConfigurate for K3sPackageManager {
+        type R = K3sMirror;
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![
+                arg!(-u --url <URL>)
+                    .help("mirror urls in priority order")
+                    .num_args(1..)
+                    .action(clap::ArgAction::Append)
+                    .required_unless_present("rank"),
+                arg!(-r --rank <COUNT>)
+                    .help("use the fastest COUNT built-in mirrors in latency order")
+                    .value_parser(clap::value_parser!(usize))
+                    .conflicts_with("url"),
+            ]
+        }
+
+        fn name(&self) -> &'static str {
+            "k3s"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            let (_, yaml) = read_config(self.get_default_profile_vec()).ok()?;
+            let config = serde_yaml::from_str::<RegistriesConfig>(&yaml).ok()?;
+            config
+                .endpoints(DOCKER_HUB)
+                .into_iter()
+                .next()
+                .map(K3sMirror::new)
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            let mirrors = include_str!("../../../mirrors/docker.json");
+            let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+            mirrors
+                .into_iter()
+                .map(|x| {
+                    let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                        delay as i128
+                    } else {
+                        -1
+                    };
+                    Self::R { url_delay, ..x }
+                })
+                .collect()
+        }
+
+        fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+            if let Some(count) = args.get_one::<usize>("rank") {
+                self.rank_mirrors(*count);
+                return;
+            }
+            let mirrors = args
+                .get_many::<String>("url")
+                .unwrap_or_default()
+                .map(|url| K3sMirror::new(url.clone()))
+                .collect();
+            self.set_mirrors(mirrors);
+        }
+
+        fn set_mirror(&self, mirror: Self::R) {
+            self.update_profiles(|config| prepend_endpoint(config, &mirror.url));
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            DEFAULT_K3S_PROFILES.to_vec()
+        }
+
+        fn remove_mirror(&self, mirror: Self::R) {
+            self.update_profiles(|config| remove_endpoints(config, &[&mirror.url]));
+        }
+
+        ///
+        /// 只移除内置镜像，保留手动配置的 endpoint
+        ///
+        fn reset_mirrors(&self) {
+            let mirrors: Vec<K3sMirror> =
+                serde_json::from_str(include_str!("../../../mirrors/docker.json"))
+                    .unwrap_or_default();
+            let urls: Vec<&str> = mirrors.iter().map(|m| m.url.as_str()).collect();
+            self.update_profiles(|config| remove_endpoints(config, &urls));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_registries() {
+            let yaml = r#"mirrors:
+  docker.io:
+    endpoint:
+    - https://registry-1.docker.io
+configs:
+  registry.example.com:
+    auth:
+      username: user
+      password: pass
+"#;
+            let new_yaml = update_registries(yaml, |config| {
+                prepend_endpoint(config, "https://docker.m.daocloud.io")
+            })
+            .unwrap();
+            assert_eq!(
+                new_yaml,
+                r#"mirrors:
+  docker.io:
+    endpoint:
+    - https://docker.m.daocloud.io
+    - https://registry-1.docker.io
+configs:
+  registry.example.com:
+    auth:
+      username: user
+      password: pass
+"#
+            );
+
+            // 只移除指定的镜像，保留手动配置的 endpoint
+            let reset_yaml = update_registries(&new_yaml, |config| {
+                remove_endpoints(config, &["https://docker.m.daocloud.io/"])
+            })
+            .unwrap();
+            assert_eq!(reset_yaml, yaml);
+
+            let new_yaml =
+                update_registries(&new_yaml, |config| config.set_endpoints(DOCKER_HUB, vec![]))
+                    .unwrap();
+            assert!(!new_yaml.contains("mirrors"));
+            assert!(new_yaml.contains("registry.example.com"));
+            assert_eq!(
+                update_registries("", |config| config.set_endpoints(DOCKER_HUB, vec![])).unwrap(),
+                ""
+            );
+            assert_eq!(
+                K3sMirror::new("https://docker.m.daocloud.io".into())
+                    .new_config()
+                    .unwrap(),
+                "mirrors:\n  docker.io:\n    endpoint:\n    - https://docker.m.daocloud.io\n"
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    impl Reader for K3sMirror {
+        fn new_config(&self) -> Result<String> {
+            unimplemented!("not support new_config for this platform")
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct K3sPackageManager {}
+
+    impl MirrorConfigurate for K3sPackageManager {
+        type R = K3sMirror;
+
+        fn support(&self) -> bool {
+            false
+        }
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![
+                arg!(-u --url <URL>)
+                    .help("mirror urls in priority order")
+                    .num_args(1..)
+                    .action(clap::ArgAction::Append)
+                    .required_unless_present("rank"),
+                arg!(-r --rank <COUNT>)
+                    .help("use the fastest COUNT built-in mirrors in latency order")
+                    .value_parser(clap::value_parser!(usize))
+                    .conflicts_with("url"),
+            ]
+        }
+
+        fn name(&self) -> &'static str {
+            "k3s"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            unimplemented!("not support current_mirror for this platform")
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            unimplemented!("not support get_mirrors for this platform")
+        }
+
+        fn set_mirror_by_args(&self, _args: &clap::ArgMatches) {
+            unimplemented!("not support set_mirror_by_args for this platform")
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            unimplemented!("not support get_default_profile_vec for this platform")
+        }
+
+        fn remove_mirror(&self, _mirror: Self::R) {
+            unimplemented!("not support remove_mirror for this platform")
+        }
+
+        fn reset_mirrors(&self) {
+            unimplemented!("not support reset_mirrors for this platform")
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+///
+/// k3s、rke2 私有仓库配置 registries.yaml
+///
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct RegistriesConfig {
+    #[serde(default, skip_serializing_if = "Mapping::is_empty")]
+    pub(super) mirrors: Mapping,
+    /// configs 等其他配置保持不变
+    #[serde(flatten)]
+    pub(super) extra_fields: Mapping,
+}
+
+impl RegistriesConfig {
+    ///
+    /// 上游仓库的 endpoint 列表
+    ///
+    pub(super) fn endpoints(&self, registry: &str) -> Vec<String> {
+        self.mirrors
+            .get(registry)
+            .and_then(|mirror| mirror.get("endpoint"))
+            .and_then(Value::as_sequence)
+            .map(|endpoints| {
+                endpoints
+                    .iter()
+                    .filter_map(|endpoint| endpoint.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    ///
+    /// 设置上游仓库的 endpoint 列表，为空时移除 endpoint，仓库没有其他配置时一并移除
+    ///
+    pub(super) fn set_endpoints(&mut self, registry: &str, endpoints: Vec<String>) {
+        let key = Value::from(registry);
+        if endpoints.is_empty() {
+            if let Some(Value::Mapping(mirror)) = self.mirrors.get_mut(&key) {
+                mirror.remove("endpoint");
+                if mirror.is_empty() {
+                    self.mirrors.remove(&key);
+                }
+            }
+            return;
+        }
+        let mirror = self
+            .mirrors
+            .entry(key)
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+        if !mirror.is_mapping() {
+            *mirror = Value::Mapping(Mapping::new());
+        }
+        let endpoints = endpoints.into_iter().map(Value::from).collect();
+        mirror
+            .as_mapping_mut()
+            .unwrap()
+            .insert("endpoint".into(), Value::Sequence(endpoints));
+    }
+}
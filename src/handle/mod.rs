@@ -8,14 +8,37 @@ use serde_json::Value;
 
 use crate::utils::file_utils::write_config;
 
+pub mod apk;
 pub mod apt;
+pub mod bun;
 pub mod cargo;
+pub mod composer;
+pub mod conda;
+pub mod containerd;
+pub mod cpan;
+pub mod cran;
+pub mod dnf;
 pub mod docker;
+pub mod flutter;
+pub mod gem;
+pub mod go;
 pub mod gradle;
+pub mod haskell;
+pub mod helm;
+pub mod hex;
+pub mod homebrew;
+pub mod julia;
+pub mod k3s;
 pub mod maven;
+pub mod nix;
 pub mod npm;
+pub mod nuget;
+pub mod opam;
 pub mod pacman;
 pub mod pip;
+pub mod podman;
+pub mod rustup;
+pub mod yarn;
 
 pub(super) trait Reader: From<serde_json::Value> {
     /// 参数输出到文件时的格式
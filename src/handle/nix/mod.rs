@@ -0,0 +1,313 @@
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+use super::{MirrorConfigurate, Reader};
+
+const ENV_NAME: &str = "XDG_CONFIG_HOME";
+
+const SUBSTITUTERS: &str = "substituters";
+
+/// 追加到系统配置的缓存，不会覆盖 /etc/nix/nix.conf 或 NixOS 中设置的 substituters
+const EXTRA_SUBSTITUTERS: &str = "extra-substituters";
+
+const TRUSTED_PUBLIC_KEYS: &str = "trusted-public-keys";
+
+const EXTRA_TRUSTED_PUBLIC_KEYS: &str = "extra-trusted-public-keys";
+
+/// 官方二进制缓存
+const DEFAULT_SUBSTITUTER: &str = "https://cache.nixos.org/";
+
+/// 用户配置优先，不存在时使用系统配置
+static DEFAULT_NIX_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let config_home = match env::var(ENV_NAME) {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => dirs::home_dir().unwrap().join(".config"),
+    };
+    vec![
+        config_home.join("nix").join("nix.conf"),
+        PathBuf::from("/etc/nix/nix.conf"),
+    ]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct NixMirror {
+    url: String,
+    /// 镜像使用的签名公钥，同步官方缓存的镜像不需要
+    #[serde(default)]
+    public_key: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl NixMirror {
+    pub fn new(url: String, public_key: String) -> Self {
+        Self {
+            url,
+            public_key,
+            url_delay: -1,
+        }
+    }
+}
+
+impl Display for NixMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for NixMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        let public_key = value["public_key"].as_str();
+        Self::new(
+            url.unwrap_or_default().to_string(),
+            public_key.unwrap_or_default().to_string(),
+        )
+    }
+}
+
+///
+/// 读取 nix.conf 中的配置项，值以空格分隔
+///
+fn get_option<'a>(conf: &'a str, key: &str) -> Option<Vec<&'a str>> {
+    conf.lines().find_map(|line| {
+        let (k, v) = line.split_once('=')?;
+        (k.trim() == key).then(|| v.split_whitespace().collect())
+    })
+}
+
+///
+/// 设置 nix.conf 中的配置项，已存在则替换，不存在则追加，值为 None 时删除
+///
+fn set_option(conf: &str, key: &str, values: Option<&[&str]>) -> String {
+    let mut new_conf = String::new();
+    let mut written = false;
+    for line in conf.lines() {
+        let matched = line.split_once('=').is_some_and(|(k, _)| k.trim() == key);
+        if !matched {
+            new_conf.push_str(line);
+            new_conf.push('\n');
+        } else if let (Some(values), false) = (values, written) {
+            new_conf.push_str(&format!("{} = {}\n", key, values.join(" ")));
+            written = true;
+        }
+    }
+    if let (Some(values), false) = (values, written) {
+        new_conf.push_str(&format!("{} = {}\n", key, values.join(" ")));
+    }
+    new_conf
+}
+
+///
+/// 将值添加到配置项的开头或末尾，配置文件中已设置该项时修改该项，
+/// 否则写入对应的 extra- 配置项，避免覆盖系统配置中的值
+///
+fn add_value(conf: &str, keys: (&str, &str), value: &str, first: bool) -> String {
+    let (key, extra_key) = keys;
+    let key = if get_option(conf, key).is_some() {
+        key
+    } else {
+        extra_key
+    };
+    let mut values = get_option(conf, key).unwrap_or_default();
+    values.retain(|v| *v != value);
+    if first {
+        values.insert(0, value);
+    } else {
+        values.push(value);
+    }
+    set_option(conf, key, Some(&values))
+}
+
+///
+/// 将镜像放在 substituters 的最前面，保留其他缓存
+///
+fn add_substituter(conf: &str, mirror: &NixMirror) -> String {
+    let mut conf = add_value(conf, (SUBSTITUTERS, EXTRA_SUBSTITUTERS), &mirror.url, true);
+    if !mirror.public_key.is_empty() {
+        conf = add_value(
+            &conf,
+            (TRUSTED_PUBLIC_KEYS, EXTRA_TRUSTED_PUBLIC_KEYS),
+            &mirror.public_key,
+            false,
+        );
+    }
+    conf
+}
+
+///
+/// 移除镜像，剩余的配置为空时删除该配置项，substituters 只剩官方缓存时同样删除
+///
+fn remove_substituters(conf: &str, mirrors: &[NixMirror]) -> String {
+    let urls: Vec<&str> = mirrors.iter().map(|m| m.url.as_str()).collect();
+    let keys: Vec<&str> = mirrors
+        .iter()
+        .map(|m| m.public_key.as_str())
+        .filter(|key| !key.is_empty())
+        .collect();
+    let mut conf = conf.to_string();
+    for (key, values) in [
+        (SUBSTITUTERS, &urls),
+        (EXTRA_SUBSTITUTERS, &urls),
+        (TRUSTED_PUBLIC_KEYS, &keys),
+        (EXTRA_TRUSTED_PUBLIC_KEYS, &keys),
+    ] {
+        if let Some(mut old) = get_option(&conf, key) {
+            let len = old.len();
+            old.retain(|value| !values.contains(value));
+            if old.len() == len {
+                continue;
+            }
+            let new = if old.is_empty() || (key == SUBSTITUTERS && old == [DEFAULT_SUBSTITUTER]) {
+                None
+            } else {
+                Some(old.as_slice())
+            };
+            conf = set_option(&conf, key, new);
+        }
+    }
+    conf
+}
+
+impl Reader for NixMirror {
+    fn new_config(&self) -> Result<String> {
+        let conf = read_config(DEFAULT_NIX_PROFILES.to_vec())
+            .map(|(_, conf)| conf)
+            .unwrap_or_default();
+        Ok(add_substituter(&conf, self))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct NixPackageManager {}
+
+impl MirrorConfigurate for NixPackageManager {
+    type R = NixMirror;
+
+    fn support(&self) -> bool {
+        !cfg!(target_os = "windows")
+    }
+
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(-u --url <url>)
+                .help("The url of the binary cache")
+                .required(true),
+            arg!(-k --key <public_key>)
+                .help(
+                    "The public key of the binary cache, not needed for mirrors of cache.nixos.org",
+                )
+                .required(false),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "nix"
+    }
+
+    fn current_mirror(&self) -> Option<NixMirror> {
+        let (_, conf) = read_config(self.get_default_profile_vec()).ok()?;
+        let url = [SUBSTITUTERS, EXTRA_SUBSTITUTERS]
+            .into_iter()
+            .filter_map(|key| get_option(&conf, key))
+            .flatten()
+            .find(|url| url.trim_end_matches('/') != DEFAULT_SUBSTITUTER.trim_end_matches('/'))?;
+        Some(NixMirror::new(url.to_string(), String::new()))
+    }
+
+    fn get_mirrors(&self) -> Vec<NixMirror> {
+        let mirrors = include_str!("../../../mirrors/nix.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let public_key = args.get_one::<String>("key").cloned().unwrap_or_default();
+        let mirror = NixMirror::new(url, public_key);
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: NixMirror) {
+        // 写回读取到的配置文件
+        let paths = match read_config(self.get_default_profile_vec()) {
+            Ok((path, _)) => vec![path],
+            Err(_) => self.get_default_profile_vec(),
+        };
+        if let Ok(new_config) = mirror.new_config() {
+            let _ = write_config(paths, &new_config);
+        }
+    }
+
+    fn remove_mirror(&self, mirror: NixMirror) {
+        if let Ok((path, conf)) = read_config(self.get_default_profile_vec()) {
+            let new_conf = remove_substituters(&conf, &[mirror]);
+            let _ = write_config(vec![path], &new_conf);
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((path, conf)) = read_config(self.get_default_profile_vec()) {
+            let mirrors: Vec<NixMirror> =
+                serde_json::from_str(include_str!("../../../mirrors/nix.json")).unwrap_or_default();
+            let new_conf = remove_substituters(&conf, &mirrors);
+            let _ = write_config(vec![path], &new_conf);
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_NIX_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substituters() {
+        let conf = "experimental-features = nix-command flakes\nsubstituters = https://cache.nixos.org/ https://nix-community.cachix.org\n";
+        let mirror = NixMirror::new(
+            "https://mirrors.tuna.tsinghua.edu.cn/nix-channels/store".into(),
+            String::new(),
+        );
+        let new_conf = add_substituter(conf, &mirror);
+        assert_eq!(
+            new_conf,
+            "experimental-features = nix-command flakes\nsubstituters = https://mirrors.tuna.tsinghua.edu.cn/nix-channels/store https://cache.nixos.org/ https://nix-community.cachix.org\n"
+        );
+        assert_eq!(remove_substituters(&new_conf, &[mirror]), conf);
+
+        let mirror = NixMirror::new(
+            "https://cache.example.com".into(),
+            "cache.example.com-1:abc=".into(),
+        );
+        // 未设置 substituters 时写入 extra- 配置项，保留系统配置中的缓存
+        let conf = "experimental-features = nix-command flakes\n";
+        let new_conf = add_substituter(conf, &mirror);
+        assert_eq!(
+            new_conf,
+            "experimental-features = nix-command flakes\nextra-substituters = https://cache.example.com\nextra-trusted-public-keys = cache.example.com-1:abc=\n"
+        );
+        assert_eq!(remove_substituters(&new_conf, &[mirror]), conf);
+    }
+}
@@ -0,0 +1,484 @@
+mod object;
+
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use object::Element;
+use process_arg_derive::ProcessArg;
+use quick_xml::{escape::escape, events::Event};
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+
+use super::{MirrorConfigurate, Reader};
+use std::{fmt::Display, path::PathBuf, sync::LazyLock};
+
+/// 官方源名称
+const NUGET_ORG_KEY: &str = "nuget.org";
+
+/// 官方源地址
+const NUGET_ORG_URL: &str = "https://api.nuget.org/v3/index.json";
+
+/// 配置文件不存在时使用的空配置
+const EMPTY_CONFIG: &str =
+    "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<configuration>\n</configuration>\n";
+
+static DEFAULT_NUGET_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let profile_dir = if cfg!(target_os = "windows") {
+        dirs::config_dir().unwrap().join("NuGet")
+    } else {
+        dirs::home_dir().unwrap().join(".nuget").join("NuGet")
+    };
+    vec![profile_dir.join("NuGet.Config")]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct NugetMirror {
+    key: String,
+    url: String,
+    /// 是否清除其他包源（包括 nuget.org）
+    #[serde(default)]
+    clear: bool,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl NugetMirror {
+    pub(crate) fn new(key: String, url: String, clear: bool) -> Self {
+        Self {
+            key,
+            url,
+            clear,
+            url_delay: -1,
+        }
+    }
+}
+
+impl Display for NugetMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} {}ms", self.key, self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for NugetMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let key = value["key"].as_str();
+        let url = value["url"].as_str();
+        let clear = value["clear"].as_bool();
+        Self::new(
+            key.unwrap_or_default().to_string(),
+            url.unwrap_or_default().to_string(),
+            clear.unwrap_or_default(),
+        )
+    }
+}
+
+///
+/// 按路径查找元素，返回元素的起始位置、开始标签的结束位置和元素的结束位置
+///
+/// 使用事件读取器定位而不是 serde 反序列化后整体写回，以保留注释、remove 元素和原有顺序；
+/// 同时不会匹配到注释中的内容或名称相近的元素
+///
+fn element_range(xml: &str, path: &[&str]) -> Option<(usize, usize, usize)> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut depth = 0;
+    loop {
+        let start = reader.buffer_position() as usize;
+        match reader.read_event().ok()? {
+            Event::Start(e) if e.name().as_ref() == path[depth].as_bytes() => {
+                let tag_end = reader.buffer_position() as usize;
+                depth += 1;
+                if depth == path.len() {
+                    reader.read_to_end(e.name()).ok()?;
+                    return Some((start, tag_end, reader.buffer_position() as usize));
+                }
+            }
+            Event::Start(e) => {
+                reader.read_to_end(e.name()).ok()?;
+            }
+            Event::Empty(e)
+                if depth == path.len() - 1 && e.name().as_ref() == path[depth].as_bytes() =>
+            {
+                let end = reader.buffer_position() as usize;
+                return Some((start, end, end));
+            }
+            Event::End(_) | Event::Eof => return None,
+            _ => {}
+        }
+    }
+}
+
+///
+/// packageSources 段在配置中的位置
+///
+fn package_sources_range(xml: &str) -> Option<(usize, usize, usize)> {
+    element_range(xml, &["configuration", "packageSources"])
+}
+
+///
+/// 读取 packageSources 段的子元素，注释等其他内容不做处理
+///
+fn package_sources(xml: &str) -> Vec<Element> {
+    let Some((start, _, end)) = package_sources_range(xml) else {
+        return vec![];
+    };
+    let mut reader = quick_xml::Reader::from_str(&xml[start..end]);
+    let mut elements = vec![];
+    let mut depth = 0;
+    loop {
+        let position = reader.buffer_position() as usize;
+        let event = match reader.read_event() {
+            // packageSources 本身
+            Ok(Event::Start(_)) if depth == 0 => {
+                depth += 1;
+                continue;
+            }
+            Ok(Event::Start(e)) => {
+                if reader.read_to_end(e.name()).is_err() {
+                    break;
+                }
+                e
+            }
+            Ok(Event::Empty(e)) if depth == 1 => e,
+            Ok(Event::Eof) | Ok(Event::End(_)) | Ok(Event::Empty(_)) | Err(_) => break,
+            Ok(_) => continue,
+        };
+        let attributes = event
+            .attributes()
+            .flatten()
+            .map(|attr| {
+                let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+                let value = attr.unescape_value().unwrap_or_default().into_owned();
+                (key, value)
+            })
+            .collect();
+        elements.push(Element {
+            name: String::from_utf8_lossy(event.name().as_ref()).into_owned(),
+            attributes,
+            start: start + position,
+            end: start + reader.buffer_position() as usize,
+        });
+    }
+    elements
+}
+
+///
+/// 行首到 position 的缩进
+///
+fn indent_of(xml: &str, position: usize) -> &str {
+    let line_start = xml[..position].rfind('\n').map_or(0, |i| i + 1);
+    let line = &xml[line_start..position];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+///
+/// 确保存在非自闭合的 packageSources 段，不存在时插入到 configuration 末尾
+///
+fn ensure_package_sources(xml: &str) -> Result<String> {
+    let mut xml = xml.to_string();
+    match package_sources_range(&xml) {
+        Some((start, tag_end, end)) if tag_end == end => {
+            let indent = indent_of(&xml, start).to_string();
+            xml.replace_range(
+                start..end,
+                &format!("<packageSources>\n{}</packageSources>", indent),
+            );
+        }
+        Some(_) => {}
+        None => {
+            let start = element_range(&xml, &["configuration"])
+                .filter(|(_, tag_end, end)| tag_end != end)
+                .and_then(|(_, _, end)| xml[..end].rfind("</"))
+                .ok_or_else(|| anyhow::anyhow!("configuration not found"))?;
+            let indent = format!("{}  ", indent_of(&xml, start));
+            xml.insert_str(
+                start - indent_of(&xml, start).len(),
+                &format!("{}<packageSources>\n{}</packageSources>\n", indent, indent),
+            );
+        }
+    }
+    Ok(xml)
+}
+
+///
+/// 删除元素，元素单独占一行时连同缩进与换行一起删除
+///
+fn remove_element(xml: &mut String, element: &Element) {
+    let indent = indent_of(xml, element.start).len();
+    let rest = &xml[element.end..];
+    let trailing = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    let rest = &rest[trailing..];
+    let line_start = element.start - indent;
+    let own_line = (line_start == 0 || xml[..line_start].ends_with('\n'))
+        && (rest.starts_with('\n') || rest.starts_with("\r\n"));
+    if own_line {
+        let newline = if rest.starts_with('\n') { 1 } else { 2 };
+        xml.replace_range(line_start..element.end + trailing + newline, "");
+    } else {
+        xml.replace_range(element.start..element.end, "");
+    }
+}
+
+///
+/// 删除所有满足条件的元素
+///
+fn remove_elements(xml: &mut String, f: impl Fn(&Element) -> bool) {
+    while let Some(element) = package_sources(xml).into_iter().find(|e| f(e)) {
+        remove_element(xml, &element);
+    }
+}
+
+///
+/// 在最后一个 clear 之后（没有时在段首）插入元素，使其不会被 clear 清除
+///
+fn insert_element(xml: &mut String, element: &str) {
+    let Some((start, tag_end, _)) = package_sources_range(xml) else {
+        return;
+    };
+    let elements = package_sources(xml);
+    let position = match elements.iter().rev().find(|e| e.is_clear()) {
+        Some(clear) => clear.end,
+        None => tag_end,
+    };
+    let indent = match elements.first() {
+        Some(first) => indent_of(xml, first.start).to_string(),
+        None => format!("{}  ", indent_of(xml, start)),
+    };
+    xml.insert_str(position, &format!("\n{}{}", indent, element));
+}
+
+fn add_element(key: &str, value: &str, protocol_version: Option<&str>) -> String {
+    let protocol_version = protocol_version
+        .map(|version| format!(" protocolVersion=\"{}\"", escape(version)))
+        .unwrap_or_default();
+    format!(
+        "<add key=\"{}\" value=\"{}\"{} />",
+        escape(key),
+        escape(value),
+        protocol_version
+    )
+}
+
+///
+/// 加入镜像源，clear 时清除其他包源
+///
+fn add_mirror(xml: &str, mirror: &NugetMirror) -> Result<String> {
+    let mut xml = ensure_package_sources(xml)?;
+    remove_elements(&mut xml, |e| {
+        e.is_add()
+            && (e.attribute("key") == Some(&mirror.key)
+                || e.attribute("value") == Some(&mirror.url)
+                || (mirror.clear && e.attribute("key") == Some(NUGET_ORG_KEY)))
+    });
+    if mirror.clear && !package_sources(&xml).iter().any(Element::is_clear) {
+        insert_element(&mut xml, "<clear />");
+    }
+    insert_element(&mut xml, &add_element(&mirror.key, &mirror.url, None));
+    Ok(xml)
+}
+
+///
+/// 移除内置镜像源并恢复 nuget.org，保留私有包源
+///
+/// 无法区分 clear 是否由用户添加（如用于隔离私有源），因此保留不动，nuget.org 插入到 clear 之后仍然生效
+///
+fn restore_nuget_org(xml: &str, mirrors: &[NugetMirror]) -> Result<String> {
+    let mut xml = ensure_package_sources(xml)?;
+    remove_elements(&mut xml, |e| {
+        (e.name == "remove" && e.attribute("key") == Some(NUGET_ORG_KEY))
+            || (e.is_add()
+                && e.attribute("key") != Some(NUGET_ORG_KEY)
+                && mirrors.iter().any(|m| e.attribute("value") == Some(&m.url)))
+    });
+    if !package_sources(&xml)
+        .iter()
+        .any(|e| e.is_add() && e.attribute("key") == Some(NUGET_ORG_KEY))
+    {
+        insert_element(
+            &mut xml,
+            &add_element(NUGET_ORG_KEY, NUGET_ORG_URL, Some("3")),
+        );
+    }
+    Ok(xml)
+}
+
+impl Reader for NugetMirror {
+    fn new_config(&self) -> Result<String> {
+        let xml = read_config(DEFAULT_NUGET_PROFILES.to_vec())
+            .map(|(_, xml)| xml)
+            .unwrap_or(EMPTY_CONFIG.to_string());
+        add_mirror(&xml, self)
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct NugetPackageManager {}
+
+impl MirrorConfigurate for NugetPackageManager {
+    type R = NugetMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(-k --key <key>)
+                .help("The key of the package source")
+                .default_value("mirrors")
+                .required(false),
+            arg!(-u --url <url>)
+                .help("The url of the package source")
+                .required(true),
+            clap::Arg::new("clear")
+                .short('c')
+                .long("clear")
+                .help("Clear other package sources, including nuget.org")
+                .action(clap::ArgAction::SetTrue),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "nuget"
+    }
+
+    fn current_mirror(&self) -> Option<NugetMirror> {
+        let (_, xml) = read_config(self.get_default_profile_vec()).ok()?;
+        let elements = package_sources(&xml);
+        let source = elements.iter().find(|e| e.is_add())?;
+        Some(NugetMirror::new(
+            source.attribute("key").unwrap_or_default().to_string(),
+            source.attribute("value").unwrap_or_default().to_string(),
+            elements.iter().any(Element::is_clear),
+        ))
+    }
+
+    fn get_mirrors(&self) -> Vec<NugetMirror> {
+        let mirrors = include_str!("../../../mirrors/nuget.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let key = args.get_one::<String>("key").cloned().unwrap_or_default();
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let clear = args.get_flag("clear");
+        let mirror = NugetMirror::new(key, url, clear);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: NugetMirror) {
+        if let Ok((_, xml)) = read_config(self.get_default_profile_vec()) {
+            let mut xml = xml;
+            remove_elements(&mut xml, |e| {
+                e.is_add() && e.attribute("value") == Some(&mirror.url)
+            });
+            let _ = write_config(self.get_default_profile_vec(), &xml);
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, xml)) = read_config(self.get_default_profile_vec()) {
+            let mirrors: Vec<NugetMirror> =
+                serde_json::from_str(include_str!("../../../mirrors/nuget.json"))
+                    .unwrap_or_default();
+            if let Ok(xml) = restore_nuget_org(&xml, &mirrors) {
+                let _ = write_config(self.get_default_profile_vec(), &xml);
+            }
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_NUGET_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_sources() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<configuration>
+  <packageSources>
+    <!-- 官方源 -->
+    <add key="nuget.org" value="https://api.nuget.org/v3/index.json" protocolVersion="3" />
+    <clear />
+    <add key="private" value="https://nuget.example.com/v3/index.json" />
+    <remove key="legacy" />
+  </packageSources>
+</configuration>
+"#;
+        let mirror = NugetMirror::new(
+            "huaweicloud".into(),
+            "https://repo.huaweicloud.com/repository/nuget/v3/index.json".into(),
+            true,
+        );
+        let new_xml = add_mirror(xml, &mirror).unwrap();
+        assert_eq!(
+            new_xml,
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<configuration>
+  <packageSources>
+    <!-- 官方源 -->
+    <clear />
+    <add key="huaweicloud" value="https://repo.huaweicloud.com/repository/nuget/v3/index.json" />
+    <add key="private" value="https://nuget.example.com/v3/index.json" />
+    <remove key="legacy" />
+  </packageSources>
+</configuration>
+"#
+        );
+
+        assert_eq!(
+            restore_nuget_org(&new_xml, &[mirror]).unwrap(),
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<configuration>
+  <packageSources>
+    <!-- 官方源 -->
+    <clear />
+    <add key="nuget.org" value="https://api.nuget.org/v3/index.json" protocolVersion="3" />
+    <add key="private" value="https://nuget.example.com/v3/index.json" />
+    <remove key="legacy" />
+  </packageSources>
+</configuration>
+"#
+        );
+    }
+
+    #[test]
+    fn test_gen() {
+        let mirror = NugetMirror::new(
+            "tencent".into(),
+            "https://mirrors.cloud.tencent.com/nuget/".into(),
+            false,
+        );
+        assert_eq!(
+            add_mirror(EMPTY_CONFIG, &mirror).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<configuration>\n  <packageSources>\n    <add key=\"tencent\" value=\"https://mirrors.cloud.tencent.com/nuget/\" />\n  </packageSources>\n</configuration>\n"
+        );
+        assert_eq!(
+            add_mirror("<configuration>\n  <packageSources />\n</configuration>\n", &mirror)
+                .unwrap(),
+            "<configuration>\n  <packageSources>\n    <add key=\"tencent\" value=\"https://mirrors.cloud.tencent.com/nuget/\" />\n  </packageSources>\n</configuration>\n"
+        );
+        // 注释中的内容和名称相近的元素不会被当作 packageSources
+        assert_eq!(
+            add_mirror(
+                "<configuration>\n  <!-- <packageSources></packageSources> -->\n  <packageSourcesX />\n</configuration>\n",
+                &mirror
+            )
+            .unwrap(),
+            "<configuration>\n  <!-- <packageSources></packageSources> -->\n  <packageSourcesX />\n  <packageSources>\n    <add key=\"tencent\" value=\"https://mirrors.cloud.tencent.com/nuget/\" />\n  </packageSources>\n</configuration>\n"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+///
+/// NuGet.Config 中 packageSources 段的子元素（add、remove、clear），位置为在整个配置中的位置
+///
+#[derive(Debug)]
+pub(super) struct Element {
+    pub(super) name: String,
+    pub(super) attributes: Vec<(String, String)>,
+    pub(super) start: usize,
+    pub(super) end: usize,
+}
+
+impl Element {
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(super) fn is_add(&self) -> bool {
+        self.name == "add"
+    }
+
+    pub(super) fn is_clear(&self) -> bool {
+        self.name == "clear"
+    }
+}
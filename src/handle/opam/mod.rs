@@ -0,0 +1,253 @@
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::{anyhow, Result};
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf, sync::LazyLock};
+
+use super::{MirrorConfigurate, Reader};
+
+const ENV_NAME: &str = "OPAMROOT";
+
+/// 切换的仓库名称
+const REPOSITORY_NAME: &str = "default";
+
+/// opam 官方仓库
+const DEFAULT_URL: &str = "https://opam.ocaml.org";
+
+static OPAM_ROOT: LazyLock<PathBuf> = LazyLock::new(|| match env::var(ENV_NAME) {
+    Ok(value) => PathBuf::from(value),
+    Err(_) if cfg!(target_os = "windows") => dirs::data_local_dir().unwrap().join("opam"),
+    Err(_) => dirs::home_dir().unwrap().join(".opam"),
+});
+
+static DEFAULT_OPAM_PROFILES: LazyLock<Vec<PathBuf>> =
+    LazyLock::new(|| vec![OPAM_ROOT.join("repo").join("repos-config")]);
+
+static DEFAULT_OPAM_CONFIG: LazyLock<Vec<PathBuf>> =
+    LazyLock::new(|| vec![OPAM_ROOT.join("config")]);
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct OpamMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl OpamMirror {
+    pub fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for OpamMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for OpamMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// repos-config 中 default 仓库地址的位置，如 "default" {"https://opam.ocaml.org"}
+///
+fn default_url_range(repos_config: &str) -> Option<(usize, usize)> {
+    let name = format!("\"{}\"", REPOSITORY_NAME);
+    let mut offset = 0;
+    while let Some(index) = repos_config[offset..].find(&name) {
+        let after = offset + index + name.len();
+        let rest = &repos_config[after..];
+        let value = rest.trim_start();
+        if let Some(value) = value.strip_prefix("{\"") {
+            let start = after + rest.len() - value.len();
+            let end = start + value.find('"')?;
+            return Some((start, end));
+        }
+        offset = after;
+    }
+    None
+}
+
+///
+/// 读取 repos-config 中 default 仓库的地址
+///
+fn default_url(repos_config: &str) -> Option<&str> {
+    default_url_range(repos_config).map(|(start, end)| &repos_config[start..end])
+}
+
+///
+/// 设置 repos-config 中 default 仓库的地址，不存在时加入 repositories 列表
+///
+fn set_default_url(repos_config: &str, url: &str) -> Result<String> {
+    let mut repos_config = repos_config.to_string();
+    match default_url_range(&repos_config) {
+        Some((start, end)) => repos_config.replace_range(start..end, url),
+        None => {
+            let start = repos_config
+                .find("repositories:")
+                .and_then(|start| Some(start + repos_config[start..].find('[')? + 1))
+                .ok_or_else(|| anyhow!("repositories not found"))?;
+            repos_config.insert_str(
+                start,
+                &format!("\n  \"{}\" {{\"{}\"}}", REPOSITORY_NAME, url),
+            );
+        }
+    }
+    Ok(repos_config)
+}
+
+///
+/// 确保 config 的 repositories 中包含 default 仓库
+///
+fn ensure_repository(config: &str) -> String {
+    let name = format!("\"{}\"", REPOSITORY_NAME);
+    let mut new_config = String::new();
+    let mut found = false;
+    for line in config.lines() {
+        match line.strip_prefix("repositories:") {
+            Some(value) => {
+                found = true;
+                let value = value.trim();
+                // 多行的列表不做修改
+                if value.contains(&name) || (value.starts_with('[') && !value.ends_with(']')) {
+                    new_config.push_str(line);
+                } else {
+                    let repositories = value.trim_start_matches('[').trim_end_matches(']').trim();
+                    new_config.push_str(&format!("repositories: [{} {}]", name, repositories));
+                }
+            }
+            None => new_config.push_str(line),
+        }
+        new_config.push('\n');
+    }
+    if !found {
+        new_config.push_str(&format!("repositories: {}\n", name));
+    }
+    new_config
+}
+
+impl Reader for OpamMirror {
+    fn new_config(&self) -> Result<String> {
+        // 需要先执行 opam init
+        let (_, repos_config) = read_config(DEFAULT_OPAM_PROFILES.to_vec())?;
+        set_default_url(&repos_config, &self.url)
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct OpamPackageManager {}
+
+impl MirrorConfigurate for OpamPackageManager {
+    type R = OpamMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![arg!(-u --url <url>)
+            .help("The url of the opam repository")
+            .required(true)]
+    }
+
+    fn name(&self) -> &'static str {
+        "opam"
+    }
+
+    fn current_mirror(&self) -> Option<OpamMirror> {
+        let (_, repos_config) = read_config(self.get_default_profile_vec()).ok()?;
+        default_url(&repos_config).map(|url| OpamMirror::new(url.to_string()))
+    }
+
+    fn get_mirrors(&self) -> Vec<OpamMirror> {
+        let mirrors = include_str!("../../../mirrors/opam.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let mirror = OpamMirror::new(url);
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: OpamMirror) {
+        if let Ok(new_config) = mirror.new_config() {
+            let _ = write_config(self.get_default_profile_vec(), &new_config);
+            if let Ok((_, config)) = read_config(DEFAULT_OPAM_CONFIG.to_vec()) {
+                let _ = write_config(DEFAULT_OPAM_CONFIG.to_vec(), &ensure_repository(&config));
+            }
+        }
+    }
+
+    fn remove_mirror(&self, mirror: OpamMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.url == mirror.url)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        self.set_mirror(OpamMirror::new(DEFAULT_URL.into()));
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_OPAM_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_default_url() {
+        let repos_config = "opam-version: \"2.0\"\nrepositories: [\n  \"default\" {\"https://opam.ocaml.org\"}\n  \"local\" {\"file:///srv/opam\"}\n]\n";
+        assert_eq!(default_url(repos_config), Some("https://opam.ocaml.org"));
+        assert_eq!(
+            set_default_url(
+                repos_config,
+                "https://mirrors.tuna.tsinghua.edu.cn/git/opam-repository.git"
+            )
+            .unwrap(),
+            "opam-version: \"2.0\"\nrepositories: [\n  \"default\" {\"https://mirrors.tuna.tsinghua.edu.cn/git/opam-repository.git\"}\n  \"local\" {\"file:///srv/opam\"}\n]\n"
+        );
+        assert_eq!(
+            set_default_url(
+                "opam-version: \"2.0\"\nrepositories: [\n]\n",
+                DEFAULT_URL
+            )
+            .unwrap(),
+            "opam-version: \"2.0\"\nrepositories: [\n  \"default\" {\"https://opam.ocaml.org\"}\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_ensure_repository() {
+        assert_eq!(
+            ensure_repository("opam-version: \"2.0\"\nrepositories: \"local\"\n"),
+            "opam-version: \"2.0\"\nrepositories: [\"default\" \"local\"]\n"
+        );
+        assert_eq!(
+            ensure_repository("opam-version: \"2.0\"\nrepositories: \"default\"\n"),
+            "opam-version: \"2.0\"\nrepositories: \"default\"\n"
+        );
+    }
+}
@@ -1,2 +1,336 @@
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use crate::utils::net_utils::test_connection;
+use anyhow::{bail, Result};
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::file_utils::read_config;
+
+use super::{write_config, MirrorConfigurate, Reader};
+
+pub(crate) use os_specific::*;
+
+/// 官方的全球镜像
+const DEFAULT_SERVER: &str = "https://geo.mirror.pkgbuild.com/$repo/os/$arch";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct PacmanMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl PacmanMirror {
+    pub(crate) fn new(url: String) -> Self {
+        // 只给出镜像站仓库根路径时，补全 $repo/os/$arch
+        let url = if url.contains("$repo") {
+            url
+        } else {
+            format!("{}/$repo/os/$arch", url.trim_end_matches('/'))
+        };
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for PacmanMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for PacmanMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// 解析 mirrorlist 中的 Server 行，返回是否启用以及镜像地址
+///
+fn parse_server_line(line: &str) -> Option<(bool, &str)> {
+    let trimmed = line.trim();
+    let uncommented = trimmed.trim_start_matches('#').trim_start();
+    let (key, value) = uncommented.split_once('=')?;
+    if key.trim() != "Server" {
+        return None;
+    }
+    Some((uncommented.len() == trimmed.len(), value.trim()))
+}
+
+///
+/// 按给定顺序启用镜像，原有的 Server 行会被注释掉而不是删除
+///
+fn rank_mirrorlist(content: &str, urls: &[String]) -> String {
+    let servers: Vec<String> = urls.iter().map(|url| format!("Server = {}", url)).collect();
+    let mut lines = vec![];
+    let mut commented = HashSet::new();
+    let mut insert_at = None;
+    for line in content.lines() {
+        match parse_server_line(line) {
+            Some((_, url)) => {
+                insert_at.get_or_insert(lines.len());
+                // 已启用的镜像及重复的注释行不再保留
+                if !urls.iter().any(|u| u == url) && commented.insert(url.to_string()) {
+                    lines.push(format!("#Server = {}", url));
+                }
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+    let insert_at = insert_at.unwrap_or(lines.len());
+    lines.splice(insert_at..insert_at, servers);
+    let mut new_content = lines.join("\n");
+    new_content.push('\n');
+    new_content
+}
+
 #[cfg(target_os = "linux")]
-static PACMAN_HOME: &str = "";
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    static DEFAULT_PACMAN_PROFILE: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+        let path = PathBuf::from("/etc/pacman.d/mirrorlist");
+        vec![path]
+    });
+
+    impl Reader for PacmanMirror {
+        fn new_config(&self) -> Result<String> {
+            match read_config(DEFAULT_PACMAN_PROFILE.to_vec()) {
+                Ok((_, mirrorlist)) => Ok(rank_mirrorlist(
+                    &mirrorlist,
+                    std::slice::from_ref(&self.url),
+                )),
+                Err(_) => bail!("Not an Arch based distribution"),
+            }
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct PacmanPackageManager {}
+
+    impl PacmanPackageManager {
+        ///
+        /// 按顺序写入多个镜像，排在前面的优先使用
+        ///
+        pub(crate) fn set_mirrors(&self, mirrors: Vec<PacmanMirror>) {
+            if let Ok((_, mirrorlist)) = read_config(self.get_default_profile_vec()) {
+                let urls: Vec<String> = mirrors.into_iter().map(|m| m.url).collect();
+                let new_config = rank_mirrorlist(&mirrorlist, &urls);
+                let _ = write_config(self.get_default_profile_vec(), &new_config);
+            }
+        }
+
+        ///
+        /// 类似 rankmirrors，按延迟从低到高启用内置镜像中最快的 count 个
+        ///
+        pub(crate) fn rank_mirrors(&self, count: usize) {
+            let mut mirrors: Vec<PacmanMirror> = self
+                .get_mirrors()
+                .into_iter()
+                .filter(|m| m.url_delay >= 0)
+                .collect();
+            mirrors.sort_by_key(|m| m.url_delay);
+            mirrors.truncate(count);
+            if !mirrors.is_empty() {
+                self.set_mirrors(mirrors);
+            }
+        }
+    }
+
+    impl MirrorConfigurate for PacmanPackageManager {
+        type R = PacmanMirror;
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![
+                arg!(-u --url <URL>)
+                    .help("mirror urls in priority order")
+                    .num_args(1..)
+                    .action(clap::ArgAction::Append)
+                    .required_unless_present("rank"),
+                arg!(-r --rank <COUNT>)
+                    .help("enable the fastest COUNT built-in mirrors in latency order")
+                    .value_parser(clap::value_parser!(usize))
+                    .conflicts_with("url"),
+            ]
+        }
+
+        fn name(&self) -> &'static str {
+            "pacman"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            if let Ok((_, mirrorlist)) = read_config(self.get_default_profile_vec()) {
+                return mirrorlist
+                    .lines()
+                    .filter_map(parse_server_line)
+                    .find(|(active, _)| *active)
+                    .map(|(_, url)| PacmanMirror::new(url.to_string()));
+            }
+            None
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            let mirrors = include_str!("../../../mirrors/pacman.json");
+            let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+            mirrors
+                .into_iter()
+                .map(|x| {
+                    let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                        delay as i128
+                    } else {
+                        -1
+                    };
+                    Self::R { url_delay, ..x }
+                })
+                .collect()
+        }
+
+        fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+            if let Some(count) = args.get_one::<usize>("rank") {
+                self.rank_mirrors(*count);
+                return;
+            }
+            let mirrors = args
+                .get_many::<String>("url")
+                .unwrap_or_default()
+                .map(|url| PacmanMirror::new(url.clone()))
+                .collect();
+            self.set_mirrors(mirrors);
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            DEFAULT_PACMAN_PROFILE.to_vec()
+        }
+
+        fn remove_mirror(&self, mirror: Self::R) {
+            if let Ok((_, mirrorlist)) = read_config(self.get_default_profile_vec()) {
+                let mut new_mirrorlist = String::new();
+                for line in mirrorlist.lines() {
+                    match parse_server_line(line) {
+                        Some((true, url)) if url == mirror.url => {
+                            new_mirrorlist.push_str(&format!("#Server = {}", url))
+                        }
+                        _ => new_mirrorlist.push_str(line),
+                    }
+                    new_mirrorlist.push('\n');
+                }
+                let _ = write_config(self.get_default_profile_vec(), &new_mirrorlist);
+            }
+        }
+
+        fn reset_mirrors(&self) {
+            self.set_mirrors(vec![PacmanMirror::new(DEFAULT_SERVER.to_string())]);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    impl Reader for PacmanMirror {
+        fn new_config(&self) -> Result<String> {
+            unimplemented!("not support new_config for this platform")
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct PacmanPackageManager {}
+
+    impl MirrorConfigurate for PacmanPackageManager {
+        type R = PacmanMirror;
+
+        fn support(&self) -> bool {
+            false
+        }
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![arg!(-u --url <URL>).help("mirror url").required(true)]
+        }
+
+        fn name(&self) -> &'static str {
+            "pacman"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            unimplemented!("not support current_mirror for this platform")
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            unimplemented!("not support get_mirrors for this platform")
+        }
+
+        fn set_mirror_by_args(&self, _args: &clap::ArgMatches) {
+            unimplemented!("not support set_mirror_by_args for this platform")
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            unimplemented!("not support get_default_profile_vec for this platform")
+        }
+
+        fn remove_mirror(&self, _mirror: Self::R) {
+            unimplemented!("not support remove_mirror for this platform")
+        }
+
+        fn reset_mirrors(&self) {
+            unimplemented!("not support reset_mirrors for this platform")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_servers() {
+        let mirrorlist = r#"## Arch Linux repository mirrorlist
+
+## Worldwide
+Server = https://geo.mirror.pkgbuild.com/$repo/os/$arch
+#Server = https://mirrors.ustc.edu.cn/archlinux/$repo/os/$arch
+"#;
+        let urls = vec![
+            "https://mirrors.ustc.edu.cn/archlinux/$repo/os/$arch".to_string(),
+            "https://mirrors.tuna.tsinghua.edu.cn/archlinux/$repo/os/$arch".to_string(),
+        ];
+        let new_mirrorlist = rank_mirrorlist(mirrorlist, &urls);
+        assert_eq!(
+            new_mirrorlist,
+            r#"## Arch Linux repository mirrorlist
+
+## Worldwide
+Server = https://mirrors.ustc.edu.cn/archlinux/$repo/os/$arch
+Server = https://mirrors.tuna.tsinghua.edu.cn/archlinux/$repo/os/$arch
+#Server = https://geo.mirror.pkgbuild.com/$repo/os/$arch
+"#
+        );
+        // 再次切换时不会产生重复的注释行
+        let reset = rank_mirrorlist(&new_mirrorlist, &[DEFAULT_SERVER.to_string()]);
+        assert_eq!(
+            reset,
+            r#"## Arch Linux repository mirrorlist
+
+## Worldwide
+Server = https://geo.mirror.pkgbuild.com/$repo/os/$arch
+#Server = https://mirrors.ustc.edu.cn/archlinux/$repo/os/$arch
+#Server = https://mirrors.tuna.tsinghua.edu.cn/archlinux/$repo/os/$arch
+"#
+        );
+    }
+}
@@ -0,0 +1,375 @@
+mod object;
+
+use std::{
+    env,
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use crate::utils::net_utils::test_connection;
+use anyhow::{bail, Result};
+use clap::arg;
+use object::{RegistriesConfig, Registry, RegistryMirror};
+use process_arg_derive::ProcessArg;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::file_utils::read_config;
+
+use super::{write_config, MirrorConfigurate, Reader};
+
+pub(crate) use os_specific::*;
+
+/// 配置镜像的上游仓库
+const DOCKER_HUB: &str = "docker.io";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct PodmanMirror {
+    url: String,
+    /// 是否写入系统配置 /etc/containers/registries.conf
+    #[serde(default)]
+    system: bool,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl PodmanMirror {
+    pub(crate) fn new(url: String, system: bool) -> Self {
+        Self {
+            url,
+            system,
+            url_delay: -1,
+        }
+    }
+}
+
+impl Display for PodmanMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for PodmanMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        let system = value["system"].as_bool();
+        Self::new(
+            url.unwrap_or_default().to_string(),
+            system.unwrap_or_default(),
+        )
+    }
+}
+
+///
+/// registries.conf 中的镜像地址不带协议，http 地址需要标记为 insecure
+///
+fn mirror_location(url: &str) -> (String, bool) {
+    let insecure = url.starts_with("http://");
+    let location = url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    (location.to_string(), insecure)
+}
+
+fn is_docker_hub(registry: &Registry) -> bool {
+    registry.prefix.as_deref().or(registry.location.as_deref()) == Some(DOCKER_HUB)
+}
+
+///
+/// 将镜像加入 docker.io 的 mirror 列表最前面，不存在 docker.io 仓库时创建
+///
+fn add_mirror(config: &mut RegistriesConfig, url: &str) {
+    let (location, insecure) = mirror_location(url);
+    let index = match config.registry.iter().position(is_docker_hub) {
+        Some(index) => index,
+        None => {
+            config.registry.push(Registry {
+                prefix: Some(DOCKER_HUB.into()),
+                location: Some(DOCKER_HUB.into()),
+                ..Default::default()
+            });
+            config.registry.len() - 1
+        }
+    };
+    let registry = &mut config.registry[index];
+    registry.mirror.retain(|mirror| mirror.location != location);
+    registry.mirror.insert(
+        0,
+        RegistryMirror {
+            location,
+            insecure: insecure.then_some(true),
+            ..Default::default()
+        },
+    );
+}
+
+///
+/// 从 docker.io 的 mirror 列表中移除镜像，只剩默认配置的 docker.io 仓库一并移除
+///
+fn remove_mirrors(config: &mut RegistriesConfig, urls: &[String]) {
+    let locations: Vec<String> = urls.iter().map(|url| mirror_location(url).0).collect();
+    for registry in config.registry.iter_mut().filter(|r| is_docker_hub(r)) {
+        registry
+            .mirror
+            .retain(|mirror| !locations.contains(&mirror.location));
+    }
+    config.registry.retain(|registry| {
+        !is_docker_hub(registry)
+            || !registry.mirror.is_empty()
+            || !registry.extra_fields.is_empty()
+            || registry.location.as_deref() != Some(DOCKER_HUB)
+    });
+}
+
+///
+/// docker.io 的第一个镜像地址
+///
+fn first_mirror(config: &RegistriesConfig) -> Option<String> {
+    let registry = config.registry.iter().find(|r| is_docker_hub(r))?;
+    let mirror = registry.mirror.first()?;
+    let scheme = if mirror.insecure == Some(true) {
+        "http"
+    } else {
+        "https"
+    };
+    Some(format!("{}://{}", scheme, mirror.location))
+}
+
+#[cfg(target_os = "linux")]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    const ENV_NAME: &str = "XDG_CONFIG_HOME";
+
+    static USER_PROFILE: LazyLock<PathBuf> = LazyLock::new(|| {
+        let config_home = match env::var(ENV_NAME) {
+            Ok(value) => PathBuf::from(value),
+            Err(_) => dirs::home_dir().unwrap().join(".config"),
+        };
+        config_home.join("containers").join("registries.conf")
+    });
+
+    static SYSTEM_PROFILE: LazyLock<PathBuf> =
+        LazyLock::new(|| PathBuf::from("/etc/containers/registries.conf"));
+
+    /// 用户配置存在时 podman 不再读取系统配置
+    static DEFAULT_PODMAN_PROFILES: LazyLock<Vec<PathBuf>> =
+        LazyLock::new(|| vec![USER_PROFILE.clone(), SYSTEM_PROFILE.clone()]);
+
+    impl PodmanMirror {
+        ///
+        /// 写入的配置文件，用户配置不存在时以系统配置为基础生成
+        ///
+        fn profiles(&self) -> Vec<PathBuf> {
+            if self.system {
+                vec![SYSTEM_PROFILE.clone()]
+            } else {
+                DEFAULT_PODMAN_PROFILES.to_vec()
+            }
+        }
+    }
+
+    impl Reader for PodmanMirror {
+        fn new_config(&self) -> Result<String> {
+            if self.url.is_empty() {
+                bail!("mirror url is empty");
+            }
+            let mut config = match read_config(self.profiles()) {
+                Ok((_, toml)) => toml::from_str::<RegistriesConfig>(&toml)?,
+                Err(_) => RegistriesConfig::default(),
+            };
+            add_mirror(&mut config, &self.url);
+            Ok(toml::to_string(&config)?)
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct PodmanPackageManager {}
+
+    impl PodmanPackageManager {
+        ///
+        /// 修改所有已存在的配置文件
+        ///
+        fn update_profiles(&self, f: impl Fn(&mut RegistriesConfig)) {
+            for path in self.get_default_profile_vec() {
+                if let Ok((_, toml)) = read_config(vec![path.clone()]) {
+                    if let Ok(mut config) = toml::from_str::<RegistriesConfig>(&toml) {
+                        f(&mut config);
+                        let toml = toml::to_string(&config).unwrap();
+                        let _ = write_config(vec![path], &toml);
+                    }
+                }
+            }
+        }
+    }
+
+    impl MirrorConfigurate for PodmanPackageManager {
+        type R = PodmanMirror;
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![
+                arg!(-u --url <URL>).help("mirror url").required(true),
+                clap::Arg::new("system")
+                    .short('s')
+                    .long("system")
+                    .help("Write to /etc/containers/registries.conf instead of the user config")
+                    .action(clap::ArgAction::SetTrue),
+            ]
+        }
+
+        fn name(&self) -> &'static str {
+            "podman"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            let (path, toml) = read_config(self.get_default_profile_vec()).ok()?;
+            let config = toml::from_str::<RegistriesConfig>(&toml).ok()?;
+            first_mirror(&config).map(|url| PodmanMirror::new(url, path == *SYSTEM_PROFILE))
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            let mirrors = include_str!("../../../mirrors/docker.json");
+            let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+            mirrors
+                .into_iter()
+                .map(|x| {
+                    let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                        delay as i128
+                    } else {
+                        -1
+                    };
+                    Self::R { url_delay, ..x }
+                })
+                .collect()
+        }
+
+        fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+            let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+            let system = args.get_flag("system");
+            let mirror = PodmanMirror::new(url, system);
+            self.set_mirror(mirror);
+        }
+
+        fn set_mirror(&self, mirror: Self::R) {
+            if let Ok(new_config) = mirror.new_config() {
+                let _ = write_config(mirror.profiles(), &new_config);
+            }
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            DEFAULT_PODMAN_PROFILES.to_vec()
+        }
+
+        fn remove_mirror(&self, mirror: Self::R) {
+            self.update_profiles(|config| {
+                remove_mirrors(config, std::slice::from_ref(&mirror.url))
+            });
+        }
+
+        fn reset_mirrors(&self) {
+            let mirrors: Vec<PodmanMirror> =
+                serde_json::from_str(include_str!("../../../mirrors/docker.json"))
+                    .unwrap_or_default();
+            let urls: Vec<String> = mirrors.into_iter().map(|m| m.url).collect();
+            self.update_profiles(|config| remove_mirrors(config, &urls));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_registries() {
+            let toml = r#"
+unqualified-search-registries = ["docker.io"]
+
+[[registry]]
+prefix = "docker.io"
+location = "docker.io"
+
+[[registry.mirror]]
+location = "mirror.gcr.io"
+"#;
+            let mut config: RegistriesConfig = toml::from_str(toml).unwrap();
+            add_mirror(&mut config, "https://docker.m.daocloud.io/");
+            assert_eq!(
+                first_mirror(&config),
+                Some("https://docker.m.daocloud.io".to_string())
+            );
+            assert_eq!(config.registry[0].mirror.len(), 2);
+            println!("{}", toml::to_string(&config).unwrap());
+
+            remove_mirrors(
+                &mut config,
+                &[
+                    "https://docker.m.daocloud.io".to_string(),
+                    "https://mirror.gcr.io".to_string(),
+                ],
+            );
+            assert!(config.registry.is_empty());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod os_specific {
+
+    use select_mirror_derive::SelectMirror;
+
+    use super::*;
+
+    impl Reader for PodmanMirror {
+        fn new_config(&self) -> Result<String> {
+            unimplemented!("not support new_config for this platform")
+        }
+    }
+
+    #[derive(ProcessArg, SelectMirror, Clone, Copy)]
+    pub(crate) struct PodmanPackageManager {}
+
+    impl MirrorConfigurate for PodmanPackageManager {
+        type R = PodmanMirror;
+
+        fn support(&self) -> bool {
+            false
+        }
+
+        fn parse_args(&self) -> Vec<clap::Arg> {
+            vec![arg!(-u --url <URL>).help("mirror url").required(true)]
+        }
+
+        fn name(&self) -> &'static str {
+            "podman"
+        }
+
+        fn current_mirror(&self) -> Option<Self::R> {
+            unimplemented!("not support current_mirror for this platform")
+        }
+
+        fn get_mirrors(&self) -> Vec<Self::R> {
+            unimplemented!("not support get_mirrors for this platform")
+        }
+
+        fn set_mirror_by_args(&self, _args: &clap::ArgMatches) {
+            unimplemented!("not support set_mirror_by_args for this platform")
+        }
+
+        fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+            unimplemented!("not support get_default_profile_vec for this platform")
+        }
+
+        fn remove_mirror(&self, _mirror: Self::R) {
+            unimplemented!("not support remove_mirror for this platform")
+        }
+
+        fn reset_mirrors(&self) {
+            unimplemented!("not support reset_mirrors for this platform")
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use toml::Value;
+
+///
+/// containers-registries.conf v2
+///
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct RegistriesConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) registry: Vec<Registry>,
+    #[serde(flatten)]
+    extra_fields: HashMap<String, Value>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct Registry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) location: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) mirror: Vec<RegistryMirror>,
+    #[serde(flatten)]
+    pub(super) extra_fields: HashMap<String, Value>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct RegistryMirror {
+    pub(super) location: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) insecure: Option<bool>,
+    #[serde(flatten)]
+    pub(super) extra_fields: HashMap<String, Value>,
+}
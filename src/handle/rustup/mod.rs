@@ -0,0 +1,197 @@
+use crate::utils::{
+    net_utils::test_connection,
+    profile_utils::{read_env_block, remove_env_block, shell_profiles, write_env_block, Shell},
+};
+use anyhow::Result;
+use clap::arg;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt::Display, path::PathBuf};
+
+use super::{MirrorConfigurate, Reader};
+
+/// shell 启动文件中配置块的名称
+const BLOCK_NAME: &str = "rustup";
+
+const DIST_SERVER: &str = "RUSTUP_DIST_SERVER";
+
+const UPDATE_ROOT: &str = "RUSTUP_UPDATE_ROOT";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct RustupMirror {
+    #[serde(default)]
+    name: String,
+    dist_server: String,
+    #[serde(default)]
+    update_root: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl RustupMirror {
+    pub(crate) fn new(name: String, dist_server: String, update_root: String) -> Self {
+        let dist_server = dist_server.trim_end_matches('/').to_string();
+        // 未指定时使用镜像站约定的 rustup 目录
+        let update_root = if update_root.is_empty() {
+            format!("{}/rustup", dist_server)
+        } else {
+            update_root.trim_end_matches('/').to_string()
+        };
+        Self {
+            name,
+            dist_server,
+            update_root,
+            url_delay: -1,
+        }
+    }
+
+    fn vars(&self) -> [(&str, &str); 2] {
+        [
+            (DIST_SERVER, self.dist_server.as_str()),
+            (UPDATE_ROOT, self.update_root.as_str()),
+        ]
+    }
+}
+
+impl Display for RustupMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "{} {}ms", self.dist_server, self.url_delay)
+        } else {
+            write!(
+                f,
+                "{}: {} {}ms",
+                self.name, self.dist_server, self.url_delay
+            )
+        }
+    }
+}
+
+impl From<serde_json::Value> for RustupMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let name = value["name"].as_str();
+        let dist_server = value["dist_server"].as_str();
+        let update_root = value["update_root"].as_str();
+        Self::new(
+            name.unwrap_or_default().to_string(),
+            dist_server.unwrap_or_default().to_string(),
+            update_root.unwrap_or_default().to_string(),
+        )
+    }
+}
+
+impl Reader for RustupMirror {
+    ///
+    /// 写入 bash、zsh 启动文件的配置块内容
+    ///
+    fn new_config(&self) -> Result<String> {
+        Ok(Shell::Posix.render_env(&self.vars()))
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct RustupPackageManager {}
+
+impl MirrorConfigurate for RustupPackageManager {
+    type R = RustupMirror;
+
+    fn support(&self) -> bool {
+        !cfg!(target_os = "windows")
+    }
+
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![
+            arg!(-d --dist <DIST_SERVER>)
+                .help("The url of RUSTUP_DIST_SERVER, e.g. https://rsproxy.cn")
+                .required(true),
+            arg!(-u --update <UPDATE_ROOT>)
+                .help("The url of RUSTUP_UPDATE_ROOT, defaults to <DIST_SERVER>/rustup")
+                .required(false),
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        "rustup"
+    }
+
+    fn current_mirror(&self) -> Option<RustupMirror> {
+        let vars = read_env_block(BLOCK_NAME);
+        let get = |key: &str| {
+            vars.iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                // 配置块不存在时读取当前环境变量
+                .or_else(|| env::var(key).ok())
+        };
+        let dist_server = get(DIST_SERVER)?;
+        let update_root = get(UPDATE_ROOT).unwrap_or_default();
+        Some(RustupMirror::new(String::new(), dist_server, update_root))
+    }
+
+    fn get_mirrors(&self) -> Vec<RustupMirror> {
+        let mirrors = include_str!("../../../mirrors/rustup.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.dist_server.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let dist_server = args.get_one::<String>("dist").cloned().unwrap_or_default();
+        let update_root = args
+            .get_one::<String>("update")
+            .cloned()
+            .unwrap_or_default();
+        let mirror = RustupMirror::new(String::new(), dist_server, update_root);
+        self.set_mirror(mirror);
+    }
+
+    fn set_mirror(&self, mirror: RustupMirror) {
+        let _ = write_env_block(BLOCK_NAME, &mirror.vars());
+    }
+
+    fn remove_mirror(&self, mirror: RustupMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.dist_server == mirror.dist_server)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        let _ = remove_env_block(BLOCK_NAME);
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        shell_profiles().into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen() {
+        let mirror = RustupMirror::new(
+            "rsproxy".into(),
+            "https://rsproxy.cn/".into(),
+            String::new(),
+        );
+        assert_eq!(
+            mirror.new_config().unwrap(),
+            "export RUSTUP_DIST_SERVER=\"https://rsproxy.cn\"\nexport RUSTUP_UPDATE_ROOT=\"https://rsproxy.cn/rustup\""
+        );
+    }
+}
@@ -0,0 +1,241 @@
+mod object;
+
+use crate::utils::{
+    file_utils::{read_config, write_config},
+    net_utils::test_connection,
+};
+use anyhow::Result;
+use clap::arg;
+use object::YarnrcConfig;
+use process_arg_derive::ProcessArg;
+use select_mirror_derive::SelectMirror;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::PathBuf, process::Command, sync::LazyLock};
+use url::Url;
+
+use super::{MirrorConfigurate, Reader};
+
+///
+/// 是否使用 Yarn Berry（2.x 及以上），无法执行 yarn 时根据 .yarnrc.yml 是否存在判断
+///
+/// 在用户主目录下执行，避免当前目录中项目的 packageManager 字段影响检测到的版本
+///
+static YARN_BERRY: LazyLock<bool> = LazyLock::new(|| {
+    let home = dirs::home_dir().unwrap();
+    match Command::new("yarn")
+        .arg("--version")
+        .current_dir(&home)
+        .output()
+    {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .split('.')
+            .next()
+            .and_then(|major| major.parse::<u32>().ok())
+            .is_some_and(|major| major >= 2),
+        _ => home.join(".yarnrc.yml").exists(),
+    }
+});
+
+static DEFAULT_YARN_PROFILES: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let profile_name = if *YARN_BERRY {
+        ".yarnrc.yml"
+    } else {
+        ".yarnrc"
+    };
+    vec![dirs::home_dir().unwrap().join(profile_name)]
+});
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct YarnMirror {
+    url: String,
+    /// The delay time of the url, in milliseconds.
+    #[serde(default)]
+    url_delay: i128,
+}
+
+impl YarnMirror {
+    pub fn new(url: String) -> Self {
+        Self { url, url_delay: -1 }
+    }
+}
+
+impl Display for YarnMirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}ms", self.url, self.url_delay)
+    }
+}
+
+impl From<serde_json::Value> for YarnMirror {
+    fn from(value: serde_json::Value) -> Self {
+        let url = value["url"].as_str();
+        Self::new(url.unwrap_or_default().to_string())
+    }
+}
+
+///
+/// http 地址的主机名，Yarn Berry 需要将其加入 unsafeHttpWhitelist
+///
+fn http_host(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    if url.scheme() != "http" {
+        return None;
+    }
+    url.host_str().map(String::from)
+}
+
+///
+/// 设置 .yarnrc.yml 中的 npmRegistryServer，url 为 None 时移除
+///
+fn berry_config(yaml: &str, url: Option<&str>) -> Result<String> {
+    let mut config = serde_yaml::from_str::<Option<YarnrcConfig>>(yaml)?.unwrap_or_default();
+    // 移除旧镜像加入的白名单
+    if let Some(host) = config.npm_registry_server.as_deref().and_then(http_host) {
+        config.unsafe_http_whitelist.retain(|h| h != &host);
+    }
+    config.npm_registry_server = url.map(String::from);
+    if let Some(host) = url.and_then(http_host) {
+        if !config.unsafe_http_whitelist.contains(&host) {
+            config.unsafe_http_whitelist.push(host);
+        }
+    }
+    Ok(serde_yaml::to_string(&config)?)
+}
+
+///
+/// 设置 .yarnrc 中的 registry，url 为 None 时移除
+///
+fn classic_config(properties: &str, url: Option<&str>) -> String {
+    let mut new_properties = String::new();
+    for line in properties.lines() {
+        if !line.starts_with("registry ") {
+            new_properties.push_str(line);
+            new_properties.push('\n');
+        }
+    }
+    if let Some(url) = url {
+        new_properties.push_str(&format!("registry \"{}\"\n", url));
+    }
+    new_properties
+}
+
+impl Reader for YarnMirror {
+    fn new_config(&self) -> Result<String> {
+        let old = read_config(DEFAULT_YARN_PROFILES.to_vec())
+            .map(|(_, old)| old)
+            .unwrap_or_default();
+        if *YARN_BERRY {
+            berry_config(&old, Some(&self.url))
+        } else {
+            Ok(classic_config(&old, Some(&self.url)))
+        }
+    }
+}
+
+#[derive(ProcessArg, SelectMirror, Clone, Copy)]
+pub(crate) struct YarnPackageManager {}
+
+impl MirrorConfigurate for YarnPackageManager {
+    type R = YarnMirror;
+    fn parse_args(&self) -> Vec<clap::Arg> {
+        vec![arg!(-u --url <url>)
+            .help("The url of the mirror")
+            .required(true)]
+    }
+
+    fn name(&self) -> &'static str {
+        "yarn"
+    }
+
+    fn current_mirror(&self) -> Option<YarnMirror> {
+        let (_, old) = read_config(self.get_default_profile_vec()).ok()?;
+        if *YARN_BERRY {
+            serde_yaml::from_str::<Option<YarnrcConfig>>(&old)
+                .ok()??
+                .npm_registry_server
+                .map(YarnMirror::new)
+        } else {
+            old.lines()
+                .find_map(|line| line.strip_prefix("registry "))
+                .map(|url| YarnMirror::new(url.trim().trim_matches('"').to_string()))
+        }
+    }
+
+    fn get_mirrors(&self) -> Vec<YarnMirror> {
+        let mirrors = include_str!("../../../mirrors/npm.json");
+        let mirrors: Vec<Self::R> = serde_json::from_str(mirrors).unwrap_or_default();
+        mirrors
+            .into_iter()
+            .map(|x| {
+                let url_delay = if let Ok((_, delay)) = test_connection(x.url.clone()) {
+                    delay as i128
+                } else {
+                    -1
+                };
+                Self::R { url_delay, ..x }
+            })
+            .collect()
+    }
+
+    fn set_mirror_by_args(&self, args: &clap::ArgMatches) {
+        let url = args.get_one::<String>("url").cloned().unwrap_or_default();
+        let mirror = YarnMirror::new(url);
+        self.set_mirror(mirror);
+    }
+
+    fn remove_mirror(&self, mirror: YarnMirror) {
+        if self
+            .current_mirror()
+            .is_some_and(|current| current.url == mirror.url)
+        {
+            self.reset_mirrors();
+        }
+    }
+
+    fn reset_mirrors(&self) {
+        if let Ok((_, old)) = read_config(self.get_default_profile_vec()) {
+            let new_config = if *YARN_BERRY {
+                berry_config(&old, None)
+            } else {
+                Ok(classic_config(&old, None))
+            };
+            if let Ok(new_config) = new_config {
+                let _ = write_config(self.get_default_profile_vec(), &new_config);
+            }
+        }
+    }
+
+    fn get_default_profile_vec(&self) -> Vec<PathBuf> {
+        DEFAULT_YARN_PROFILES.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_berry_config() {
+        let yaml = "enableTelemetry: false\nnpmRegistryServer: \"http://mirrors.cloud.tencent.com/npm/\"\nunsafeHttpWhitelist:\n  - mirrors.cloud.tencent.com\n  - localhost\n";
+        let new_yaml = berry_config(yaml, Some("https://registry.npmmirror.com")).unwrap();
+        assert_eq!(
+            new_yaml,
+            "npmRegistryServer: https://registry.npmmirror.com\nunsafeHttpWhitelist:\n- localhost\nenableTelemetry: false\n"
+        );
+        let http_yaml = berry_config("", Some("http://mirrors.cloud.tencent.com/npm/")).unwrap();
+        assert_eq!(
+            http_yaml,
+            "npmRegistryServer: http://mirrors.cloud.tencent.com/npm/\nunsafeHttpWhitelist:\n- mirrors.cloud.tencent.com\n"
+        );
+    }
+
+    #[test]
+    fn test_classic_config() {
+        let properties =
+            "registry \"https://registry.yarnpkg.com\"\nlastUpdateCheck 1700000000000\n";
+        assert_eq!(
+            classic_config(properties, Some("https://registry.npmmirror.com")),
+            "lastUpdateCheck 1700000000000\nregistry \"https://registry.npmmirror.com\"\n"
+        );
+    }
+}
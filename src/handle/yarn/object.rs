@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+
+///
+/// Yarn Berry 的 .yarnrc.yml
+///
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct YarnrcConfig {
+    #[serde(rename = "npmRegistryServer", skip_serializing_if = "Option::is_none")]
+    pub(super) npm_registry_server: Option<String>,
+    #[serde(
+        rename = "unsafeHttpWhitelist",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub(super) unsafe_http_whitelist: Vec<String>,
+    /// 其他配置保持原有顺序
+    #[serde(flatten)]
+    extra_fields: Mapping,
+}
@@ -1,3 +1,4 @@
 pub mod file_utils;
 pub mod net_utils;
+pub mod profile_utils;
 pub mod string_utils;
@@ -0,0 +1,264 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{bail, Result};
+
+use super::file_utils::read_config;
+
+///
+/// 受管理配置块的起止标记
+///
+fn markers(name: &str) -> (String, String) {
+    (
+        format!("# >>> mirrors {} >>>", name),
+        format!("# <<< mirrors {} <<<", name),
+    )
+}
+
+///
+/// 替换文本中的受管理配置块，不存在时追加到末尾，body 为 None 时移除该配置块
+///
+/// 配置块缺少结束标记时返回错误，避免将标记之后用户自己的配置一并删除
+///
+pub(crate) fn replace_block(content: &str, name: &str, body: Option<&str>) -> Result<String> {
+    let (begin, end) = markers(name);
+    let source: Vec<&str> = content.lines().collect();
+    let mut lines: Vec<&str> = vec![];
+    let mut found = false;
+    let mut i = 0;
+    while i < source.len() {
+        if source[i].trim() != begin {
+            lines.push(source[i]);
+            i += 1;
+            continue;
+        }
+        let Some(len) = source[i + 1..].iter().position(|line| line.trim() == end) else {
+            bail!("missing `{}` after `{}`", end, begin);
+        };
+        match (body, found) {
+            (Some(body), false) => {
+                lines.push(&begin);
+                lines.extend(body.lines());
+                lines.push(&end);
+            }
+            // 移除追加配置块时在其前面添加的一个空行
+            _ => {
+                if lines.last().is_some_and(|line| line.is_empty()) {
+                    lines.pop();
+                }
+            }
+        }
+        found = true;
+        i += len + 2;
+    }
+    if let (Some(body), false) = (body, found) {
+        if !lines.is_empty() {
+            lines.push("");
+        }
+        lines.push(&begin);
+        lines.extend(body.lines());
+        lines.push(&end);
+    }
+    if lines.is_empty() {
+        Ok(String::new())
+    } else {
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+///
+/// 获取受管理配置块中的内容，缺少结束标记时返回 None
+///
+pub(crate) fn get_block(content: &str, name: &str) -> Option<String> {
+    let (begin, end) = markers(name);
+    let mut lines = content.lines().skip_while(|line| line.trim() != begin);
+    lines.next()?;
+    let mut body = vec![];
+    for line in lines {
+        if line.trim() == end {
+            return Some(body.join("\n"));
+        }
+        body.push(line);
+    }
+    None
+}
+
+///
+/// shell 类型
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Shell {
+    /// bash、zsh 等使用 export 的 shell
+    Posix,
+    Fish,
+}
+
+impl Shell {
+    ///
+    /// 生成设置环境变量的语句
+    ///
+    pub(crate) fn render_env(&self, vars: &[(&str, &str)]) -> String {
+        vars.iter()
+            .map(|(key, value)| match self {
+                Shell::Posix => format!("export {}=\"{}\"", key, value),
+                Shell::Fish => format!("set -gx {} \"{}\"", key, value),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    ///
+    /// 解析设置环境变量的语句
+    ///
+    pub(crate) fn parse_env(body: &str) -> Vec<(String, String)> {
+        body.lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                let (key, value) = if let Some(rest) = line.strip_prefix("export ") {
+                    rest.split_once('=')?
+                } else {
+                    line.strip_prefix("set -gx ")?.split_once(' ')?
+                };
+                Some((
+                    key.trim().to_string(),
+                    value.trim().trim_matches('"').to_string(),
+                ))
+            })
+            .collect()
+    }
+}
+
+///
+/// 需要写入环境变量的 shell 启动文件，均不存在时根据 $SHELL 选择
+///
+pub(crate) fn shell_profiles() -> Vec<(PathBuf, Shell)> {
+    let home = dirs::home_dir().unwrap();
+    let config_home = match env::var("XDG_CONFIG_HOME") {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => home.join(".config"),
+    };
+    let bash = (home.join(".bashrc"), Shell::Posix);
+    let zsh = (home.join(".zshrc"), Shell::Posix);
+    let fish = (config_home.join("fish").join("config.fish"), Shell::Fish);
+    let profiles: Vec<(PathBuf, Shell)> = [bash.clone(), zsh.clone(), fish.clone()]
+        .into_iter()
+        .filter(|(path, _)| path.exists())
+        .collect();
+    if !profiles.is_empty() {
+        return profiles;
+    }
+    let shell = env::var("SHELL").unwrap_or_default();
+    if shell.ends_with("zsh") {
+        vec![zsh]
+    } else if shell.ends_with("fish") {
+        vec![fish]
+    } else {
+        vec![bash]
+    }
+}
+
+///
+/// 将配置块写入文件，内容未发生变化时不写入
+///
+pub(crate) fn write_block(path: PathBuf, name: &str, body: Option<&str>) -> Result<()> {
+    let old = read_config(vec![path.clone()])
+        .map(|(_, old)| old)
+        .unwrap_or_default();
+    let new = replace_block(&old, name, body)?;
+    if new != old {
+        if let Some(dir_name) = path.parent() {
+            std::fs::create_dir_all(dir_name)?;
+        }
+        std::fs::write(path, new)?;
+    }
+    Ok(())
+}
+
+///
+/// 在所有 shell 启动文件中写入环境变量配置块
+///
+pub(crate) fn write_env_block(name: &str, vars: &[(&str, &str)]) -> Result<()> {
+    for (path, shell) in shell_profiles() {
+        write_block(path, name, Some(&shell.render_env(vars)))?;
+    }
+    Ok(())
+}
+
+///
+/// 从所有 shell 启动文件中移除环境变量配置块
+///
+pub(crate) fn remove_env_block(name: &str) -> Result<()> {
+    for (path, _) in shell_profiles() {
+        if path.exists() {
+            write_block(path, name, None)?;
+        }
+    }
+    Ok(())
+}
+
+///
+/// 读取 shell 启动文件中配置块设置的环境变量
+///
+pub(crate) fn read_env_block(name: &str) -> Vec<(String, String)> {
+    shell_profiles()
+        .into_iter()
+        .find_map(|(path, _)| {
+            let (_, content) = read_config(vec![path]).ok()?;
+            get_block(&content, name)
+        })
+        .map(|body| Shell::parse_env(&body))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_block() {
+        let content = "alias ll='ls -l'\n";
+        let body = Shell::Posix.render_env(&[("RUSTUP_DIST_SERVER", "https://rsproxy.cn")]);
+        let added = replace_block(content, "rustup", Some(&body)).unwrap();
+        assert_eq!(
+            added,
+            "alias ll='ls -l'\n\n# >>> mirrors rustup >>>\nexport RUSTUP_DIST_SERVER=\"https://rsproxy.cn\"\n# <<< mirrors rustup <<<\n"
+        );
+        assert_eq!(
+            Shell::parse_env(&get_block(&added, "rustup").unwrap()),
+            vec![(
+                "RUSTUP_DIST_SERVER".to_string(),
+                "https://rsproxy.cn".to_string()
+            )]
+        );
+        let body = Shell::Fish.render_env(&[("RUSTUP_DIST_SERVER", "https://rsproxy.cn")]);
+        let replaced = replace_block(&added, "rustup", Some(&body)).unwrap();
+        assert_eq!(
+            replaced,
+            "alias ll='ls -l'\n\n# >>> mirrors rustup >>>\nset -gx RUSTUP_DIST_SERVER \"https://rsproxy.cn\"\n# <<< mirrors rustup <<<\n"
+        );
+        assert_eq!(replace_block(&replaced, "rustup", None).unwrap(), content);
+
+        // 只移除追加时添加的空行，保留用户原有的空行
+        let content = "alias ll='ls -l'\n\n";
+        let added = replace_block(content, "rustup", Some(&body)).unwrap();
+        assert_eq!(replace_block(&added, "rustup", None).unwrap(), content);
+    }
+
+    #[test]
+    fn test_replace_truncated_block() {
+        let content = "# >>> mirrors rustup >>>\nexport RUSTUP_DIST_SERVER=\"https://rsproxy.cn\"\nalias ll='ls -l'\nexport PATH=\"$HOME/.cargo/bin:$PATH\"\n";
+        let body = Shell::Posix.render_env(&[("RUSTUP_DIST_SERVER", "https://rsproxy.cn")]);
+        assert!(replace_block(content, "rustup", Some(&body)).is_err());
+        assert!(replace_block(content, "rustup", None).is_err());
+        assert_eq!(get_block(content, "rustup"), None);
+
+        let dir = env::temp_dir().join("mirrors-test-truncated-block");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".bashrc");
+        std::fs::write(&path, content).unwrap();
+        assert!(write_block(path.clone(), "rustup", None).is_err());
+        let kept = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(kept.contains("alias ll='ls -l'"));
+        assert!(kept.contains("export PATH=\"$HOME/.cargo/bin:$PATH\""));
+    }
+}